@@ -0,0 +1,483 @@
+// Builds and validates deployment and admin messages, printing the JSON and the secretcli command.
+//
+// cargo run --example deploy -- <command> [--config deploy.toml] [--<option> <value>]...
+//
+// Options can be given as flags or as top level keys of a TOML file, with flags taking
+// precedence. Dashes and underscores are interchangeable in option names.
+//
+// init: buttcoin-address, buttcoin-contract-hash, starting-block, end-block, release-per-block,
+//   viewing-key, release-per-block-denominator?, max-total-emission?, code-id?, label?
+// add-recipient: contract, recipient-address, recipient-contract-hash, weight
+// set-recipient-weight: contract, address, weight
+// set-schedule: contract, end-block, release-per-block, release-per-block-denominator?,
+//   max-total-emission?, starting-block?
+// change-admin: contract, admin
+//
+// Every command also takes from? (the secretcli key, a by default) and secretcli-flags? (the
+// flags added to the secretcli command, DEFAULT_SECRETCLI_FLAGS by default).
+use cosmwasm_std::{to_vec, HumanAddr, Uint128};
+use cw_buttcoin_distributor::contract::{MAX_TOTAL_EMISSION, MAX_TOTAL_WEIGHT};
+use cw_buttcoin_distributor::msg::{ButtcoinDistributorHandleMsg, InitMsg};
+use cw_buttcoin_distributor::state::SecretContract;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env::args;
+use std::fs::read_to_string;
+use std::process::exit;
+
+// Secret addresses are bech32 encoded 20 byte addresses with the secret human readable part.
+const ADDRESS_BYTES: usize = 20;
+const ADDRESS_HRP: &str = "secret";
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const DEFAULT_SECRETCLI_FLAGS: &str =
+    "-y --keyring-backend test --gas 3000000 --gas-prices=3.0uscrt";
+const MIN_VIEWING_KEY_LENGTH: usize = 16;
+
+struct Options {
+    values: HashMap<String, String>,
+}
+
+impl Options {
+    fn parse(arguments: &[String]) -> Result<Options, String> {
+        let mut flags = HashMap::new();
+        let mut arguments = arguments.iter();
+        while let Some(flag) = arguments.next() {
+            let name = match flag.strip_prefix("--") {
+                Some(name) => name.replace('-', "_"),
+                None => return Err(format!("Expected an option but got {}.", flag)),
+            };
+            let value = arguments
+                .next()
+                .ok_or_else(|| format!("--{} is missing a value.", name))?;
+            flags.insert(name, value.clone());
+        }
+
+        let mut values = HashMap::new();
+        if let Some(path) = flags.remove("config") {
+            let contents = read_to_string(&path).map_err(|error| format!("{}: {}", path, error))?;
+            let table: toml::value::Table =
+                toml::from_str(&contents).map_err(|error| format!("{}: {}", path, error))?;
+            for (name, value) in table {
+                let value = match value {
+                    toml::Value::String(value) => value,
+                    toml::Value::Integer(value) => value.to_string(),
+                    _ => {
+                        return Err(format!(
+                            "{} in {} must be a string or an integer.",
+                            name, path
+                        ))
+                    }
+                };
+                values.insert(name.replace('-', "_"), value);
+            }
+        }
+        values.extend(flags);
+
+        Ok(Options { values })
+    }
+
+    fn optional(&mut self, name: &str) -> Option<String> {
+        self.values.remove(name)
+    }
+
+    fn required(&mut self, name: &str) -> Result<String, String> {
+        self.optional(name)
+            .ok_or_else(|| format!("{} is required.", name.replace('_', "-")))
+    }
+
+    fn optional_number<T: std::str::FromStr>(&mut self, name: &str) -> Result<Option<T>, String> {
+        self.optional(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("{} must be a whole number.", name.replace('_', "-")))
+            })
+            .transpose()
+    }
+
+    fn required_number<T: std::str::FromStr>(&mut self, name: &str) -> Result<T, String> {
+        self.optional_number(name)?
+            .ok_or_else(|| format!("{} is required.", name.replace('_', "-")))
+    }
+
+    fn address(&mut self, name: &str) -> Result<HumanAddr, String> {
+        let address = self.required(name)?;
+        validate_address(name, &address)?;
+        Ok(HumanAddr(address))
+    }
+
+    fn secret_contract(&mut self, prefix: &str) -> Result<SecretContract, String> {
+        let address = self.address(&format!("{}_address", prefix))?;
+        let contract_hash = self.required(&format!("{}_contract_hash", prefix))?;
+        validate_contract_hash(&contract_hash)?;
+        Ok(SecretContract {
+            address,
+            contract_hash,
+        })
+    }
+
+    // Unused options are most likely typos, so they are refused rather than ignored.
+    fn finish(self) -> Result<(), String> {
+        let mut unknown: Vec<String> = self.values.keys().cloned().collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+
+        unknown.sort();
+        Err(format!("Unknown options: {}.", unknown.join(", ")))
+    }
+}
+
+struct Schedule {
+    end_block: u64,
+    max_total_emission: Option<Uint128>,
+    release_per_block: Uint128,
+    release_per_block_denominator: Option<u64>,
+}
+
+// BIP-173 checksum of the human readable part and data.
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = (checksum & 0x01ff_ffff) << 5 ^ *value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+
+    checksum
+}
+
+// Returns the human readable part and the bytes of a lowercase bech32 string with a valid checksum.
+fn decode_bech32(address: &str) -> Option<(&str, Vec<u8>)> {
+    let separator = address.rfind('1')?;
+    let hrp = &address[..separator];
+    let data = address[separator + 1..]
+        .chars()
+        .map(|c| BECH32_CHARSET.find(c).map(|value| value as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    if hrp.is_empty() || data.len() < 6 || !hrp.bytes().all(|c| (33..=126).contains(&c)) {
+        return None;
+    }
+    let mut values: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|c| c & 31));
+    values.extend(&data);
+    if bech32_polymod(&values) != 1 {
+        return None;
+    }
+
+    // Regroups the 5 bit values without the checksum into bytes, which can't leave padding behind
+    let mut bytes = vec![];
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    for value in &data[..data.len() - 6] {
+        bits = bits << 5 | *value as u32;
+        bit_count += 5;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+    if bit_count >= 5 || bits != 0 {
+        return None;
+    }
+
+    Some((hrp, bytes))
+}
+
+fn validate_address(name: &str, address: &str) -> Result<(), String> {
+    match decode_bech32(address) {
+        Some((hrp, bytes)) if hrp == ADDRESS_HRP && bytes.len() == ADDRESS_BYTES => Ok(()),
+        _ => Err(format!(
+            "{} must be a bech32 address starting with {}1.",
+            name.replace('_', "-"),
+            ADDRESS_HRP
+        )),
+    }
+}
+
+fn validate_contract_hash(contract_hash: &str) -> Result<(), String> {
+    match hex::decode(contract_hash) {
+        Ok(bytes) if bytes.len() == 32 => Ok(()),
+        _ => Err(format!(
+            "{} is not a contract hash, which is 64 hexadecimal characters.",
+            contract_hash
+        )),
+    }
+}
+
+// Checks the block ordering when the starting block is known and returns the planned emission.
+fn validate_schedule(starting_block: Option<u64>, schedule: &Schedule) -> Result<u128, String> {
+    let denominator = schedule.release_per_block_denominator.unwrap_or(1);
+    if denominator == 0 {
+        return Err("release-per-block-denominator must be greater than zero.".to_string());
+    }
+    if schedule.release_per_block.u128() == 0 {
+        return Err("release-per-block must be greater than zero.".to_string());
+    }
+    if let Some(max_total_emission) = schedule.max_total_emission {
+        if max_total_emission.u128() > MAX_TOTAL_EMISSION {
+            return Err(format!(
+                "max-total-emission can not be more than {}.",
+                MAX_TOTAL_EMISSION
+            ));
+        }
+    }
+    let starting_block = match starting_block {
+        Some(starting_block) => starting_block,
+        None => return Ok(0),
+    };
+    if schedule.end_block <= starting_block {
+        return Err("end-block must be after starting-block.".to_string());
+    }
+
+    let planned_emission = ((schedule.end_block - starting_block) as u128)
+        .checked_mul(schedule.release_per_block.u128())
+        .ok_or_else(|| "The total emission does not fit in 128 bits.".to_string())?
+        / denominator as u128;
+    if planned_emission == 0 {
+        return Err("The schedule does not emit any Buttcoin.".to_string());
+    }
+    if let Some(max_total_emission) = schedule.max_total_emission {
+        if max_total_emission.u128() == 0 {
+            return Err("max-total-emission must be greater than zero.".to_string());
+        }
+        if max_total_emission.u128() < planned_emission {
+            eprintln!(
+                "Warning: max-total-emission of {} stops the schedule before it emits {}.",
+                max_total_emission, planned_emission
+            );
+        }
+    }
+
+    Ok(planned_emission)
+}
+
+// Recipient weights add up to at most MAX_TOTAL_WEIGHT, so no single weight can be more.
+fn weight(options: &mut Options) -> Result<Uint128, String> {
+    let weight = options.required_number("weight")?;
+    if weight > MAX_TOTAL_WEIGHT {
+        return Err(format!("weight can not be more than {}.", MAX_TOTAL_WEIGHT));
+    }
+
+    Ok(Uint128(weight))
+}
+
+// A viewing key needs to be long and not made up of a single kind of character.
+fn validate_viewing_key(viewing_key: &str) -> Result<(), String> {
+    let character_kinds = [
+        viewing_key.chars().any(|c| c.is_ascii_lowercase()),
+        viewing_key.chars().any(|c| c.is_ascii_uppercase()),
+        viewing_key.chars().any(|c| c.is_ascii_digit()),
+        viewing_key.chars().any(|c| !c.is_ascii_alphanumeric()),
+    ]
+    .iter()
+    .filter(|present| **present)
+    .count();
+    if viewing_key.len() < MIN_VIEWING_KEY_LENGTH || character_kinds < 3 {
+        return Err(format!(
+            "viewing-key must be at least {} characters and mix at least three of lowercase, uppercase, digits and symbols.",
+            MIN_VIEWING_KEY_LENGTH
+        ));
+    }
+
+    Ok(())
+}
+
+fn schedule(options: &mut Options) -> Result<Schedule, String> {
+    Ok(Schedule {
+        end_block: options.required_number("end_block")?,
+        max_total_emission: options.optional_number("max_total_emission")?.map(Uint128),
+        release_per_block: Uint128(options.required_number("release_per_block")?),
+        release_per_block_denominator: options.optional_number("release_per_block_denominator")?,
+    })
+}
+
+fn json<T: Serialize>(msg: &T) -> String {
+    String::from_utf8(to_vec(msg).unwrap()).unwrap()
+}
+
+// Wraps in single quotes for the shell.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn build_init(mut options: Options) -> Result<(), String> {
+    let buttcoin = options.secret_contract("buttcoin")?;
+    let starting_block = options.required_number("starting_block")?;
+    let schedule = schedule(&mut options)?;
+    let viewing_key = options.required("viewing_key")?;
+    let code_id = options
+        .optional("code_id")
+        .unwrap_or_else(|| "$CODE_ID".to_string());
+    let label = options
+        .optional("label")
+        .unwrap_or_else(|| "buttcoin-distributor".to_string());
+    let from = options.optional("from").unwrap_or_else(|| "a".to_string());
+    let secretcli_flags = options
+        .optional("secretcli_flags")
+        .unwrap_or_else(|| DEFAULT_SECRETCLI_FLAGS.to_string());
+    options.finish()?;
+    let planned_emission = validate_schedule(Some(starting_block), &schedule)?;
+    // The contract defaults max_total_emission to the planned emission
+    if schedule.max_total_emission.is_none() && planned_emission > MAX_TOTAL_EMISSION {
+        return Err(format!(
+            "The total emission can not be more than {}, set max-total-emission to cap it.",
+            MAX_TOTAL_EMISSION
+        ));
+    }
+    validate_viewing_key(&viewing_key)?;
+
+    let msg = json(&InitMsg {
+        buttcoin,
+        end_block: schedule.end_block,
+        max_total_emission: schedule.max_total_emission,
+        release_per_block: schedule.release_per_block,
+        release_per_block_denominator: schedule.release_per_block_denominator,
+        starting_block,
+        viewing_key,
+    });
+    eprintln!("Planned emission: {}", planned_emission);
+    println!("{}", msg);
+    println!(
+        "secretcli tx compute instantiate {} {} --from {} --label {} {}",
+        code_id,
+        quote(&msg),
+        from,
+        quote(&label),
+        secretcli_flags
+    );
+
+    Ok(())
+}
+
+fn build_handle(command: &str, mut options: Options) -> Result<(), String> {
+    let msg = match command {
+        "add-recipient" => ButtcoinDistributorHandleMsg::AddRecipient {
+            delivery_mode: None,
+            recipient: options.secret_contract("recipient")?,
+            weight: weight(&mut options)?,
+        },
+        "change-admin" => ButtcoinDistributorHandleMsg::ChangeAdmin {
+            admin: options.address("admin")?,
+        },
+        "set-recipient-weight" => ButtcoinDistributorHandleMsg::SetRecipientWeight {
+            address: options.address("address")?,
+            weight: weight(&mut options)?,
+        },
+        "set-schedule" => {
+            let starting_block = options.optional_number("starting_block")?;
+            let schedule = schedule(&mut options)?;
+            validate_schedule(starting_block, &schedule)?;
+            ButtcoinDistributorHandleMsg::SetSchedule {
+                end_block: schedule.end_block,
+                max_total_emission: schedule.max_total_emission,
+                release_per_block: schedule.release_per_block,
+                release_per_block_denominator: schedule.release_per_block_denominator,
+            }
+        }
+        _ => return Err(format!("Unknown command {}.", command)),
+    };
+    let contract = options.address("contract")?;
+    let from = options.optional("from").unwrap_or_else(|| "a".to_string());
+    let secretcli_flags = options
+        .optional("secretcli_flags")
+        .unwrap_or_else(|| DEFAULT_SECRETCLI_FLAGS.to_string());
+    options.finish()?;
+
+    let msg = json(&msg);
+    println!("{}", msg);
+    println!(
+        "secretcli tx compute execute {} {} --from {} {}",
+        contract,
+        quote(&msg),
+        from,
+        secretcli_flags
+    );
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: deploy <init|add-recipient|set-recipient-weight|set-schedule|change-admin> [--config deploy.toml] [--<option> <value>]...");
+        exit(1);
+    }
+
+    let result = Options::parse(&args[2..]).and_then(|options| match args[1].as_str() {
+        "init" => build_init(options),
+        command => build_handle(command, options),
+    });
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "secret18vd8fpwxzck93qlwghaj6arh4p7c5n8978vsyg";
+
+    #[test]
+    fn test_validate_address() {
+        // = When the address is a secret bech32 address
+        // = * It accepts it
+        assert_eq!(validate_address("address", ADDRESS), Ok(()));
+
+        // = When the address is not a secret bech32 address of 20 bytes
+        // = * It raises an error
+        let error = Err("address must be a bech32 address starting with secret1.".to_string());
+        for address in &[
+            "",
+            "secret1",
+            // A character changed, which breaks the checksum
+            "secret18vd8fpwxzck93qlwghaj6arh4p7c5n8978vsyh",
+            // Uppercase
+            "SECRET18VD8FPWXZCK93QLWGHAJ6ARH4P7C5N8978VSYG",
+            // b is not in the bech32 character set
+            "secret1bvd8fpwxzck93qlwghaj6arh4p7c5n8978vsyg",
+            // A valid bech32 address with another human readable part
+            "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu",
+            // A valid bech32 address of 32 bytes
+            "secret1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5z5tpwxqergd3c8g7rusq6tn66r",
+            // The same length as a secret address without being bech32
+            "secret1xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx",
+        ] {
+            assert_eq!(validate_address("address", address), error, "{}", address);
+        }
+    }
+
+    #[test]
+    fn test_validate_contract_hash() {
+        // = When the contract hash is 64 hexadecimal characters
+        // = * It accepts it
+        assert_eq!(validate_contract_hash(&"4cD7".repeat(16)), Ok(()));
+
+        // = When the contract hash is not 64 hexadecimal characters
+        // = * It raises an error
+        for contract_hash in &["", "abc", &"a".repeat(62), &"a".repeat(66), &"g".repeat(64)] {
+            assert_eq!(
+                validate_contract_hash(contract_hash),
+                Err(format!(
+                    "{} is not a contract hash, which is 64 hexadecimal characters.",
+                    contract_hash
+                ))
+            );
+        }
+    }
+}
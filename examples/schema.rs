@@ -1,19 +1,214 @@
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use cw_buttcoin_distributor::msg::{
     ButtcoinDistributorHandleAnswer, ButtcoinDistributorHandleMsg, ButtcoinDistributorQueryAnswer,
-    ButtcoinDistributorQueryMsg, InitMsg,
+    ButtcoinDistributorQueryMsg, ButtcoinDistributorReceiveMsg, InitMsg,
+    YieldOptimizerDepositButtcoinHookMsg, YieldOptimizerReceiveMsg,
+};
+use cw_buttcoin_distributor::state::{SecretContract, State};
+use schemars::schema::{
+    ArrayValidation, InstanceType, ObjectValidation, RootSchema, Schema, SchemaObject, SingleOrVec,
 };
 use std::env::current_dir;
-use std::fs::create_dir_all;
+use std::ffi::OsStr;
+use std::fs::{create_dir_all, read_dir, remove_file, write};
+use std::path::Path;
 
+#[allow(dead_code)]
 fn main() {
-    let mut out_dir = current_dir().unwrap();
-    out_dir.push("schema");
-    create_dir_all(&out_dir).unwrap();
-    remove_schemas(&out_dir).unwrap();
-    export_schema(&schema_for!(ButtcoinDistributorHandleAnswer), &out_dir);
-    export_schema(&schema_for!(ButtcoinDistributorHandleMsg), &out_dir);
-    export_schema(&schema_for!(ButtcoinDistributorQueryAnswer), &out_dir);
-    export_schema(&schema_for!(ButtcoinDistributorQueryMsg), &out_dir);
-    export_schema(&schema_for!(InitMsg), &out_dir);
+    let out_dir = current_dir().unwrap();
+    export_schemas(&out_dir.join("schema"), &out_dir.join("ts"));
+}
+
+// Writes the JSON schema of every message, answer and public state type, and a TypeScript
+// definition generated from each of them. tests/schema.rs uses this to check that the committed
+// files are up to date.
+pub fn export_schemas(schema_dir: &Path, ts_dir: &Path) {
+    let schemas = vec![
+        schema_for!(ButtcoinDistributorHandleAnswer),
+        schema_for!(ButtcoinDistributorHandleMsg),
+        schema_for!(ButtcoinDistributorQueryAnswer),
+        schema_for!(ButtcoinDistributorQueryMsg),
+        schema_for!(ButtcoinDistributorReceiveMsg),
+        schema_for!(InitMsg),
+        schema_for!(SecretContract),
+        schema_for!(State),
+        schema_for!(YieldOptimizerDepositButtcoinHookMsg),
+        schema_for!(YieldOptimizerReceiveMsg),
+    ];
+
+    create_dir_all(schema_dir).unwrap();
+    remove_schemas(schema_dir).unwrap();
+    create_dir_all(ts_dir).unwrap();
+    for entry in read_dir(ts_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension() == Some(OsStr::new("ts")) {
+            remove_file(path).unwrap();
+        }
+    }
+    for schema in &schemas {
+        export_schema(schema, schema_dir);
+        let title = title(&schema.schema);
+        write(
+            ts_dir.join(format!("{}.ts", file_name(&title))),
+            typescript(&title, schema),
+        )
+        .unwrap();
+    }
+}
+
+// The same snake case name that export_schema gives the JSON file.
+fn file_name(title: &str) -> String {
+    let mut name = String::new();
+    for (i, c) in title.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+fn title(schema: &SchemaObject) -> String {
+    schema
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.title.clone())
+        .unwrap()
+}
+
+// === TYPESCRIPT ===
+
+fn typescript(title: &str, schema: &RootSchema) -> String {
+    let mut ts = format!(
+        "// Generated from schema/{}.json by `cargo schema`. Do not edit by hand.\n\n",
+        file_name(title)
+    );
+    ts.push_str(&format!(
+        "export type {} = {};\n",
+        title,
+        ts_object_type(&schema.schema, 0)
+    ));
+    for (name, definition) in &schema.definitions {
+        ts.push_str(&format!(
+            "\nexport type {} = {};\n",
+            name,
+            ts_type(definition, 0)
+        ));
+    }
+    ts
+}
+
+fn ts_type(schema: &Schema, indent: usize) -> String {
+    match schema {
+        Schema::Bool(true) => "unknown".to_string(),
+        Schema::Bool(false) => "never".to_string(),
+        Schema::Object(schema) => ts_object_type(schema, indent),
+    }
+}
+
+fn ts_object_type(schema: &SchemaObject, indent: usize) -> String {
+    if let Some(reference) = &schema.reference {
+        return reference.trim_start_matches("#/definitions/").to_string();
+    }
+    if let Some(values) = &schema.enum_values {
+        return values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<String>>()
+            .join(" | ");
+    }
+    if let Some(subschemas) = &schema.subschemas {
+        if let Some(schemas) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+            return union(schemas, " | ", indent);
+        }
+        if let Some(schemas) = &subschemas.all_of {
+            return union(schemas, " & ", indent);
+        }
+    }
+
+    match &schema.instance_type {
+        Some(SingleOrVec::Single(instance_type)) => ts_instance_type(instance_type, schema, indent),
+        Some(SingleOrVec::Vec(instance_types)) => instance_types
+            .iter()
+            .map(|instance_type| ts_instance_type(instance_type, schema, indent))
+            .collect::<Vec<String>>()
+            .join(" | "),
+        None => "unknown".to_string(),
+    }
+}
+
+fn ts_instance_type(instance_type: &InstanceType, schema: &SchemaObject, indent: usize) -> String {
+    match instance_type {
+        InstanceType::Array => ts_array(schema.array.as_deref(), indent),
+        InstanceType::Boolean => "boolean".to_string(),
+        InstanceType::Integer | InstanceType::Number => "number".to_string(),
+        InstanceType::Null => "null".to_string(),
+        InstanceType::Object => ts_object(schema.object.as_deref(), indent),
+        InstanceType::String => "string".to_string(),
+    }
+}
+
+fn ts_array(array: Option<&ArrayValidation>, indent: usize) -> String {
+    match array.and_then(|array| array.items.as_ref()) {
+        Some(SingleOrVec::Single(item)) => {
+            let item = ts_type(item, indent);
+            if item.contains(' ') {
+                format!("({})[]", item)
+            } else {
+                format!("{}[]", item)
+            }
+        }
+        Some(SingleOrVec::Vec(items)) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(|item| ts_type(item, indent))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        None => "unknown[]".to_string(),
+    }
+}
+
+fn ts_object(object: Option<&ObjectValidation>, indent: usize) -> String {
+    let object = match object {
+        Some(object) => object,
+        None => return "Record<string, never>".to_string(),
+    };
+    if object.properties.is_empty() {
+        return match &object.additional_properties {
+            Some(value) if **value != Schema::Bool(false) => {
+                format!("Record<string, {}>", ts_type(value, indent))
+            }
+            _ => "Record<string, never>".to_string(),
+        };
+    }
+
+    let padding = "  ".repeat(indent + 1);
+    let mut ts = "{\n".to_string();
+    for (name, property) in &object.properties {
+        let optional = if object.required.contains(name) {
+            ""
+        } else {
+            "?"
+        };
+        ts.push_str(&format!(
+            "{}{}{}: {};\n",
+            padding,
+            name,
+            optional,
+            ts_type(property, indent + 1)
+        ));
+    }
+    ts.push_str(&"  ".repeat(indent));
+    ts.push('}');
+    ts
+}
+
+fn union(schemas: &[Schema], separator: &str, indent: usize) -> String {
+    schemas
+        .iter()
+        .map(|schema| ts_type(schema, indent))
+        .collect::<Vec<String>>()
+        .join(separator)
 }
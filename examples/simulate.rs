@@ -0,0 +1,447 @@
+// Simulates an emission program offline by driving the contract against mock dependencies.
+//
+// cargo run --example simulate -- init.json script.json [output.csv]
+//
+// init.json is the InitMsg the contract would be instantiated with. script.json is a list of
+// actions in block order, for example:
+// [
+//   {"add_recipient": {"block": 100, "recipient": {"address": "pool-a", "contract_hash": "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"}, "weight": "1"}},
+//   {"fund": {"block": 100, "amount": "500000"}},
+//   {"claim": {"block": 1000, "recipient": "pool-a", "amount": null}},
+//   {"set_schedule": {"block": 2000, "end_block": 5000, "max_total_emission": null, "release_per_block": "50", "release_per_block_denominator": null}},
+//   {"set_recipient_weight": {"block": 3000, "address": "pool-a", "weight": "2"}}
+// ]
+//
+// For every range of blocks between actions, and from the last action until the end of the
+// schedule, it prints the accrued, claimed and remaining Buttcoin of each recipient. Remaining is
+// what the recipient will still accrue until the end of the schedule as it stands. Balance is the
+// Buttcoin the distributor holds: what has been funded minus what its messages have paid out.
+// Like the SNIP-20, a claim that pays out more than the distributor holds fails, which stops the
+// simulation.
+use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{from_binary, from_slice, Env, Extern, HumanAddr, StdError, StdResult, Uint128};
+use cw_buttcoin_distributor::contract::{handle, init, query};
+use cw_buttcoin_distributor::msg::{
+    ButtcoinDistributorHandleMsg, ButtcoinDistributorQueryAnswer, ButtcoinDistributorQueryMsg,
+    InitMsg,
+};
+use cw_buttcoin_distributor::state::SecretContract;
+use cw_buttcoin_distributor::testing::{buttcoin_paid_out, execute_or_revert};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env::args;
+use std::fs::{read, write};
+use std::process::exit;
+
+const ADMIN: &str = "simulator-admin";
+// The mock API only canonicalizes addresses up to this length, which fits secret1 addresses.
+const CANONICAL_LENGTH: usize = 45;
+
+type MockDeps = Extern<MockStorage, MockApi, MockQuerier>;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    AddRecipient {
+        block: u64,
+        recipient: SecretContract,
+        weight: Uint128,
+    },
+    Claim {
+        amount: Option<Uint128>,
+        block: u64,
+        recipient: HumanAddr,
+    },
+    Fund {
+        amount: Uint128,
+        block: u64,
+    },
+    SetRecipientWeight {
+        address: HumanAddr,
+        block: u64,
+        weight: Uint128,
+    },
+    SetSchedule {
+        block: u64,
+        end_block: u64,
+        max_total_emission: Option<Uint128>,
+        release_per_block: Uint128,
+        release_per_block_denominator: Option<u64>,
+    },
+}
+
+impl Action {
+    fn block(&self) -> u64 {
+        match self {
+            Action::AddRecipient { block, .. }
+            | Action::Claim { block, .. }
+            | Action::Fund { block, .. }
+            | Action::SetRecipientWeight { block, .. }
+            | Action::SetSchedule { block, .. } => *block,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Row {
+    accrued: u128,
+    balance: u128,
+    claimed: u128,
+    from_block: u64,
+    recipient: HumanAddr,
+    remaining: u128,
+    to_block: u64,
+}
+
+struct Simulation {
+    balance: u128,
+    buttcoin: HumanAddr,
+    claimed: HashMap<HumanAddr, u128>,
+    deps: MockDeps,
+    rows: Vec<Row>,
+}
+
+impl Simulation {
+    // Takes what the messages pay out of the distributor's balance, reverting the handle when the
+    // balance isn't enough.
+    fn execute(
+        &mut self,
+        sender: &str,
+        block: u64,
+        msg: ButtcoinDistributorHandleMsg,
+    ) -> StdResult<()> {
+        let balance = self.balance;
+        let buttcoin = &self.buttcoin;
+        let paid_out = execute_or_revert(&mut self.deps, |deps| {
+            let response = handle(deps, env_at_block(sender, block), msg)?;
+            let paid_out = buttcoin_paid_out(buttcoin, &response.messages)?;
+            if paid_out > balance {
+                return Err(StdError::generic_err(format!(
+                    "Block {}: insufficient funds: balance={}, required={}",
+                    block, balance, paid_out
+                )));
+            }
+            Ok(paid_out)
+        })?;
+        self.balance -= paid_out;
+
+        Ok(())
+    }
+
+    fn apply(&mut self, action: Action) -> StdResult<()> {
+        match action {
+            Action::AddRecipient {
+                block,
+                recipient,
+                weight,
+            } => {
+                self.execute(
+                    ADMIN,
+                    block,
+                    ButtcoinDistributorHandleMsg::AddRecipient {
+                        delivery_mode: None,
+                        recipient: recipient.clone(),
+                        weight,
+                    },
+                )?;
+                self.execute(
+                    recipient.address.as_str(),
+                    block,
+                    ButtcoinDistributorHandleMsg::AcceptRecipient {
+                        contract_hash: recipient.contract_hash,
+                    },
+                )
+            }
+            Action::Claim {
+                amount,
+                block,
+                recipient,
+            } => {
+                let accrued_before = self.pending(&recipient, block)?;
+                self.execute(
+                    recipient.as_str(),
+                    block,
+                    ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                        amount,
+                        hook: None,
+                        recipient: None,
+                    },
+                )?;
+                let claimed_amount = accrued_before - self.pending(&recipient, block)?;
+                *self.claimed.entry(recipient).or_default() += claimed_amount;
+                Ok(())
+            }
+            // Sent the way the SNIP-20 sends it, so that the distributor sees the funding
+            Action::Fund { amount, block } => {
+                let buttcoin = self.buttcoin.clone();
+                self.execute(
+                    buttcoin.as_str(),
+                    block,
+                    ButtcoinDistributorHandleMsg::Receive {
+                        sender: HumanAddr::from(ADMIN),
+                        from: HumanAddr::from(ADMIN),
+                        amount,
+                        msg: None,
+                    },
+                )?;
+                self.balance = self.balance.saturating_add(amount.u128());
+                Ok(())
+            }
+            Action::SetRecipientWeight {
+                address,
+                block,
+                weight,
+            } => self.execute(
+                ADMIN,
+                block,
+                ButtcoinDistributorHandleMsg::SetRecipientWeight { address, weight },
+            ),
+            Action::SetSchedule {
+                block,
+                end_block,
+                max_total_emission,
+                release_per_block,
+                release_per_block_denominator,
+            } => self.execute(
+                ADMIN,
+                block,
+                ButtcoinDistributorHandleMsg::SetSchedule {
+                    end_block,
+                    max_total_emission,
+                    release_per_block,
+                    release_per_block_denominator,
+                },
+            ),
+        }
+    }
+
+    fn end_block(&self) -> StdResult<u64> {
+        match self.query(ButtcoinDistributorQueryMsg::Config {})? {
+            ButtcoinDistributorQueryAnswer::Config { end_block, .. } => Ok(end_block),
+            _ => panic!("unexpected answer"),
+        }
+    }
+
+    fn pending(&self, recipient: &HumanAddr, block: u64) -> StdResult<u128> {
+        match self.query(ButtcoinDistributorQueryMsg::Pending {
+            block,
+            recipient: Some(recipient.clone()),
+        })? {
+            ButtcoinDistributorQueryAnswer::Pending { amount } => Ok(amount.u128()),
+            _ => panic!("unexpected answer"),
+        }
+    }
+
+    fn query(&self, msg: ButtcoinDistributorQueryMsg) -> StdResult<ButtcoinDistributorQueryAnswer> {
+        from_binary(&query(&self.deps, msg)?)
+    }
+
+    fn record(&mut self, from_block: u64, to_block: u64) -> StdResult<()> {
+        let recipients = match self.query(ButtcoinDistributorQueryMsg::Recipients {})? {
+            ButtcoinDistributorQueryAnswer::Recipients { recipients } => recipients,
+            _ => panic!("unexpected answer"),
+        };
+        let end_block = self.end_block()?.max(to_block);
+        for recipient in recipients {
+            let address = recipient.contract.address;
+            let accrued = self.pending(&address, to_block)?;
+            let remaining = self.pending(&address, end_block)? - accrued;
+            self.rows.push(Row {
+                accrued,
+                balance: self.balance,
+                claimed: self.claimed.get(&address).copied().unwrap_or_default(),
+                from_block,
+                recipient: address,
+                remaining,
+                to_block,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn env_at_block(sender: &str, height: u64) -> Env {
+    let mut env = mock_env(sender, &[]);
+    env.block.height = height;
+    env
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> T {
+    let contents = read(path).unwrap_or_else(|error| {
+        eprintln!("Could not read {}: {}", path, error);
+        exit(1)
+    });
+    from_slice(&contents).unwrap_or_else(|error| {
+        eprintln!("Could not parse {}: {}", path, error);
+        exit(1)
+    })
+}
+
+fn simulate(init_msg: InitMsg, actions: Vec<Action>) -> StdResult<Vec<Row>> {
+    let first_block = actions
+        .first()
+        .map(Action::block)
+        .unwrap_or(init_msg.starting_block)
+        .min(init_msg.starting_block);
+    let mut simulation = Simulation {
+        balance: 0,
+        buttcoin: init_msg.buttcoin.address.clone(),
+        claimed: HashMap::new(),
+        deps: mock_dependencies(CANONICAL_LENGTH, &[]),
+        rows: vec![],
+    };
+    init(
+        &mut simulation.deps,
+        env_at_block(ADMIN, first_block),
+        init_msg,
+    )?;
+
+    let mut from_block = first_block;
+    for action in actions {
+        let block = action.block();
+        if block > from_block {
+            simulation.record(from_block, block)?;
+            from_block = block;
+        }
+        simulation.apply(action)?;
+    }
+    let end_block = simulation.end_block()?;
+    if end_block > from_block {
+        simulation.record(from_block, end_block)?;
+    }
+
+    Ok(simulation.rows)
+}
+
+fn main() {
+    let args: Vec<String> = args().collect();
+    if args.len() < 3 || args.len() > 4 {
+        eprintln!("Usage: simulate <init.json> <script.json> [output.csv]");
+        exit(1);
+    }
+    let init_msg: InitMsg = read_json(&args[1]);
+    let actions: Vec<Action> = read_json(&args[2]);
+    if actions
+        .windows(2)
+        .any(|pair| pair[0].block() > pair[1].block())
+    {
+        eprintln!("Actions must be in block order.");
+        exit(1);
+    }
+
+    let blocks = init_msg.end_block.saturating_sub(init_msg.starting_block) as u128;
+    let released = blocks.checked_mul(init_msg.release_per_block.u128());
+    let release_per_block_denominator = init_msg.release_per_block_denominator.unwrap_or(1);
+    // init refuses a zero denominator and a schedule that overflows
+    let rows = simulate(init_msg, actions).unwrap_or_else(|error| {
+        eprintln!("Simulation failed: {}", error);
+        exit(1)
+    });
+    let planned_emission = released.unwrap() / release_per_block_denominator as u128;
+
+    println!("Planned emission: {}", planned_emission);
+    println!(
+        "{:>10} {:>10} {:<45} {:>20} {:>20} {:>20} {:>20}",
+        "From", "To", "Recipient", "Accrued", "Claimed", "Remaining", "Balance"
+    );
+    let mut csv = String::from("from_block,to_block,recipient,accrued,claimed,remaining,balance\n");
+    for row in &rows {
+        println!(
+            "{:>10} {:>10} {:<45} {:>20} {:>20} {:>20} {:>20}",
+            row.from_block,
+            row.to_block,
+            row.recipient,
+            row.accrued,
+            row.claimed,
+            row.remaining,
+            row.balance
+        );
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            row.from_block,
+            row.to_block,
+            row.recipient,
+            row.accrued,
+            row.claimed,
+            row.remaining,
+            row.balance
+        ));
+    }
+    if let Some(path) = args.get(3) {
+        write(path, csv).unwrap_or_else(|error| {
+            eprintln!("Could not write {}: {}", path, error);
+            exit(1)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_msg() -> InitMsg {
+        InitMsg {
+            buttcoin: SecretContract {
+                address: HumanAddr::from("buttcoin"),
+                contract_hash: "b".repeat(64),
+            },
+            end_block: 200,
+            max_total_emission: None,
+            release_per_block: Uint128(10),
+            release_per_block_denominator: None,
+            starting_block: 100,
+            viewing_key: "viewing-key".to_string(),
+        }
+    }
+
+    fn script(claim_block: u64) -> Vec<Action> {
+        from_slice(
+            format!(
+                r#"[
+                    {{"add_recipient": {{"block": 100, "recipient": {{"address": "pool-a", "contract_hash": "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"}}, "weight": "1"}}}},
+                    {{"fund": {{"block": 100, "amount": "500"}}}},
+                    {{"claim": {{"block": {}, "recipient": "pool-a", "amount": null}}}}
+                ]"#,
+                claim_block
+            )
+            .as_bytes(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_simulate() {
+        // = When the distributor holds enough for a claim
+        // = * It pays the claim out of the balance and shows every range of blocks
+        assert_eq!(
+            simulate(init_msg(), script(150)).unwrap(),
+            vec![
+                Row {
+                    accrued: 500,
+                    balance: 500,
+                    claimed: 0,
+                    from_block: 100,
+                    recipient: HumanAddr::from("pool-a"),
+                    remaining: 500,
+                    to_block: 150,
+                },
+                Row {
+                    accrued: 500,
+                    balance: 0,
+                    claimed: 500,
+                    from_block: 150,
+                    recipient: HumanAddr::from("pool-a"),
+                    remaining: 0,
+                    to_block: 200,
+                }
+            ]
+        );
+
+        // = When a claim pays out more than the distributor holds
+        // = * It fails like the SNIP-20 would
+        assert_eq!(
+            simulate(init_msg(), script(160)).unwrap_err(),
+            StdError::generic_err("Block 160: insufficient funds: balance=500, required=600")
+        );
+    }
+}
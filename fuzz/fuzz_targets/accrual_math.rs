@@ -0,0 +1,134 @@
+// Runs arbitrary schedules and weights through claims and checks the payouts against the
+// emission worked out independently of the contract.
+#![no_main]
+use arbitrary::Arbitrary;
+use cosmwasm_std::{from_binary, HumanAddr, Uint128};
+use cw_buttcoin_distributor::contract::{query, MAX_TOTAL_EMISSION, REWARD_PRECISION};
+use cw_buttcoin_distributor::msg::{
+    ButtcoinDistributorHandleMsg, ButtcoinDistributorQueryAnswer, ButtcoinDistributorQueryMsg,
+    InitMsg,
+};
+use cw_buttcoin_distributor_fuzz::{
+    add_recipient, buttcoin, env, handle_or_revert, init_deps, paid_out, recipient, FuzzDeps,
+};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Claim {
+    blocks: u32,
+    recipient: u8,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    claims: Vec<Claim>,
+    duration: u64,
+    release_per_block: u128,
+    release_per_block_denominator: u64,
+    starting_block: u64,
+    weights: Vec<u64>,
+}
+
+fn scheduled_emission(input: &Input, end_block: u64, block: u64) -> Option<u128> {
+    let blocks = block.min(end_block).saturating_sub(input.starting_block) as u128;
+    Some(blocks.checked_mul(input.release_per_block)? / input.release_per_block_denominator as u128)
+}
+
+fn pending(deps: &FuzzDeps, address: HumanAddr, block: u64) -> u128 {
+    let answer = query(
+        deps,
+        ButtcoinDistributorQueryMsg::Pending {
+            block,
+            recipient: Some(address),
+        },
+    )
+    .unwrap();
+    match from_binary(&answer).unwrap() {
+        ButtcoinDistributorQueryAnswer::Pending { amount } => amount.u128(),
+        _ => panic!("unexpected answer"),
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let end_block = input.starting_block.saturating_add(input.duration);
+    let weights: Vec<u128> = input
+        .weights
+        .iter()
+        .take(4)
+        .map(|weight| *weight as u128)
+        .collect();
+    let mut deps = match init_deps(InitMsg {
+        buttcoin: buttcoin(),
+        end_block,
+        max_total_emission: None,
+        release_per_block: Uint128(input.release_per_block),
+        release_per_block_denominator: Some(input.release_per_block_denominator),
+        starting_block: input.starting_block,
+        viewing_key: "viewing-key".to_string(),
+    }) {
+        Ok(deps) => deps,
+        Err(error) => {
+            // Only empty schedules and ones too large for the reward math are refused
+            assert!(
+                end_block == input.starting_block
+                    || input.release_per_block == 0
+                    || input.release_per_block_denominator == 0
+                    || !matches!(
+                        scheduled_emission(&input, end_block, end_block),
+                        Some(emission) if emission <= MAX_TOTAL_EMISSION
+                    ),
+                "{}",
+                error
+            );
+            return;
+        }
+    };
+    assert!(scheduled_emission(&input, end_block, end_block).unwrap() <= MAX_TOTAL_EMISSION);
+    // Weights that add up to more than MAX_TOTAL_WEIGHT are refused, and nothing can overflow
+    // once the schedule and the weights have been accepted
+    for (i, weight) in weights.iter().enumerate() {
+        if add_recipient(&mut deps, recipient(i as u8), *weight, input.starting_block).is_err() {
+            return;
+        }
+    }
+
+    let mut block = input.starting_block;
+    let mut paid: u128 = 0;
+    for claim in &input.claims {
+        if weights.is_empty() {
+            break;
+        }
+        block = block.saturating_add(claim.blocks as u64);
+        let claimant = recipient(claim.recipient % weights.len() as u8);
+        let expected = pending(&deps, claimant.address.clone(), block);
+        let response = handle_or_revert(
+            &mut deps,
+            env(claimant.address.as_str(), block),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        let amount = paid_out(&response.messages);
+        assert_eq!(amount, expected);
+        paid += amount;
+        assert_eq!(pending(&deps, claimant.address, block), 0);
+
+        // What has been paid out and what is still pending only differs by rounding. Less than
+        // one Buttcoin per recipient and the pool's remainder is left out, and each pending amount
+        // can include the whole Buttcoin that the leftovers add up to, which only one recipient
+        // gets.
+        let pending_amounts: Vec<u128> = (0..weights.len())
+            .map(|i| pending(&deps, recipient(i as u8).address, block))
+            .collect();
+        let scheduled = scheduled_emission(&input, end_block, block).unwrap();
+        let accounted = paid + pending_amounts.iter().sum::<u128>();
+        let total_weight: u128 = weights.iter().sum();
+        let rounding = weights.len() as u128 + total_weight / REWARD_PRECISION + 1;
+        assert!(paid <= scheduled);
+        assert!(accounted <= scheduled + weights.len() as u128);
+        assert!(total_weight == 0 || scheduled <= accounted + rounding);
+    }
+});
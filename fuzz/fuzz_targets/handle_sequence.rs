@@ -0,0 +1,87 @@
+// Drives the contract with sequences of handle and query messages deserialized from arbitrary
+// bytes, sent by random senders at increasing block heights.
+//
+// Every line of the input is a call: "<sender> <blocks> <message>", where sender indexes senders(),
+// blocks is how far the chain moves before the call and message is the JSON of a handle or query
+// message. Lines that don't parse are skipped.
+#![no_main]
+use cosmwasm_std::{from_slice, HumanAddr, Uint128};
+use cw_buttcoin_distributor::contract::query;
+use cw_buttcoin_distributor::msg::{
+    ButtcoinDistributorHandleMsg, ButtcoinDistributorQueryMsg, InitMsg,
+};
+use cw_buttcoin_distributor_fuzz::{
+    add_recipient, buttcoin, env, handle_or_revert, init_deps, paid_out, recipient, state, ADMIN,
+};
+use libfuzzer_sys::fuzz_target;
+
+fn senders() -> Vec<HumanAddr> {
+    vec![
+        HumanAddr::from(ADMIN),
+        buttcoin().address,
+        recipient(0).address,
+        recipient(1).address,
+        recipient(2).address,
+        HumanAddr::from("user"),
+    ]
+}
+
+fn parse_call(line: &[u8]) -> Option<(usize, u16, &[u8])> {
+    let mut parts = line.splitn(3, |byte| *byte == b' ');
+    let sender = std::str::from_utf8(parts.next()?).ok()?.parse().ok()?;
+    let blocks = std::str::from_utf8(parts.next()?).ok()?.parse().ok()?;
+
+    Some((sender, blocks, parts.next()?))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut block: u64 = 100;
+    let mut deps = init_deps(InitMsg {
+        buttcoin: buttcoin(),
+        end_block: 10_100,
+        max_total_emission: None,
+        release_per_block: Uint128(1_000),
+        release_per_block_denominator: Some(3),
+        starting_block: block,
+        viewing_key: "viewing-key".to_string(),
+    })
+    .unwrap();
+    add_recipient(&mut deps, recipient(0), 1, block).unwrap();
+    add_recipient(&mut deps, recipient(1), 3, block).unwrap();
+    let senders = senders();
+    let mut paid: u128 = 0;
+
+    for line in data.split(|byte| *byte == b'\n') {
+        let (sender, blocks, msg) = match parse_call(line) {
+            Some(call) => call,
+            None => continue,
+        };
+        let sender = senders[sender % senders.len()].as_str();
+        block = block.saturating_add(blocks as u64);
+
+        if let Ok(msg) = from_slice::<ButtcoinDistributorHandleMsg>(msg) {
+            // Unlocking gives back Buttcoin that was locked rather than emitted
+            let unlock = matches!(msg, ButtcoinDistributorHandleMsg::UnlockButtcoin { .. });
+            let before = state(&deps);
+            if let Ok(response) = handle_or_revert(&mut deps, env(sender, block), msg) {
+                if !unlock {
+                    paid += paid_out(&response.messages);
+                }
+            }
+            let after = state(&deps);
+
+            assert!(after.last_update_block >= before.last_update_block);
+            assert!(after.total_emitted.u128() <= after.max_total_emission.u128());
+            assert!(paid <= after.total_emitted.u128());
+            // Locked Buttcoin is never released as rewards
+            if before.gauge_voting {
+                assert!(
+                    after.total_emitted.u128()
+                        <= before.total_emitted.u128().max(after.total_funded.u128())
+                );
+            }
+        } else if let Ok(msg) = from_slice::<ButtcoinDistributorQueryMsg>(msg) {
+            let _ = query(&deps, msg);
+        }
+    }
+});
@@ -0,0 +1,111 @@
+// Mock chain shared by the fuzz targets.
+use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier};
+use cosmwasm_std::{
+    CosmosMsg, Env, Extern, HandleResponse, HumanAddr, ReadonlyStorage, StdResult, Storage, Uint128,
+};
+use cw_buttcoin_distributor::contract::{handle, init};
+use cw_buttcoin_distributor::msg::{ButtcoinDistributorHandleMsg, InitMsg};
+use cw_buttcoin_distributor::state::{config_read, SecretContract, State};
+use cw_buttcoin_distributor::testing::{buttcoin_paid_out, execute_or_revert};
+use std::collections::BTreeMap;
+
+pub const ADMIN: &str = "admin";
+
+pub type FuzzDeps = Extern<FuzzStorage, MockApi, MockQuerier>;
+
+// Storage that can be copied, so that a failed handle can be rolled back the way the chain
+// reverts a failed transaction.
+#[derive(Clone, Default)]
+pub struct FuzzStorage {
+    data: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl ReadonlyStorage for FuzzStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.data.get(key).cloned()
+    }
+}
+
+impl Storage for FuzzStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.data.insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.data.remove(key);
+    }
+}
+
+pub fn buttcoin() -> SecretContract {
+    SecretContract {
+        address: HumanAddr::from("buttcoin"),
+        contract_hash: "b".repeat(64),
+    }
+}
+
+pub fn recipient(i: u8) -> SecretContract {
+    SecretContract {
+        address: HumanAddr(format!("recipient-{}", i)),
+        contract_hash: i.to_string().repeat(64),
+    }
+}
+
+pub fn env(sender: &str, height: u64) -> Env {
+    let mut env = mock_env(sender, &[]);
+    env.block.height = height;
+    env
+}
+
+pub fn init_deps(msg: InitMsg) -> StdResult<FuzzDeps> {
+    let mut deps = Extern {
+        storage: FuzzStorage::default(),
+        api: MockApi::new(20),
+        querier: MockQuerier::new(&[]),
+    };
+    init(&mut deps, env(ADMIN, msg.starting_block), msg)?;
+
+    Ok(deps)
+}
+
+// Registers the recipient and has it accept straight away.
+pub fn add_recipient(
+    deps: &mut FuzzDeps,
+    recipient: SecretContract,
+    weight: u128,
+    height: u64,
+) -> StdResult<()> {
+    handle_or_revert(
+        deps,
+        env(ADMIN, height),
+        ButtcoinDistributorHandleMsg::AddRecipient {
+            delivery_mode: None,
+            recipient: recipient.clone(),
+            weight: Uint128(weight),
+        },
+    )?;
+    handle_or_revert(
+        deps,
+        env(recipient.address.as_str(), height),
+        ButtcoinDistributorHandleMsg::AcceptRecipient {
+            contract_hash: recipient.contract_hash,
+        },
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_or_revert(
+    deps: &mut FuzzDeps,
+    env: Env,
+    msg: ButtcoinDistributorHandleMsg,
+) -> StdResult<HandleResponse> {
+    execute_or_revert(deps, |deps| handle(deps, env, msg))
+}
+
+pub fn paid_out(messages: &[CosmosMsg]) -> u128 {
+    buttcoin_paid_out(&buttcoin().address, messages).unwrap()
+}
+
+pub fn state(deps: &FuzzDeps) -> State {
+    config_read(&deps.storage).load().unwrap()
+}
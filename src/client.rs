@@ -0,0 +1,291 @@
+// Typed messages and queries for contracts that integrate with the Buttcoin distributor, in the
+// same way secret_toolkit::snip20 wraps SNIP-20 calls.
+use crate::msg::{
+    ButtcoinDistributorHandleMsg, ButtcoinDistributorQueryAnswer, ButtcoinDistributorQueryMsg,
+    RecipientInfo, YieldOptimizerReceiveMsg,
+};
+use crate::state::{AccrualBucket, ClaimLimits, SecretContract};
+use cosmwasm_std::{
+    from_binary, to_binary, Binary, CosmosMsg, HumanAddr, Querier, QueryRequest, StdError,
+    StdResult, Uint128, WasmMsg, WasmQuery,
+};
+
+// === HANDLE ===
+
+pub fn accept_recipient_msg(
+    contract: &SecretContract,
+    contract_hash: String,
+) -> StdResult<CosmosMsg> {
+    handle_msg(
+        contract,
+        &ButtcoinDistributorHandleMsg::AcceptRecipient { contract_hash },
+    )
+}
+
+pub fn add_claim_operator_msg(
+    contract: &SecretContract,
+    operator: HumanAddr,
+) -> StdResult<CosmosMsg> {
+    handle_msg(
+        contract,
+        &ButtcoinDistributorHandleMsg::AddClaimOperator { operator },
+    )
+}
+
+pub fn claim_buttcoin_msg(contract: &SecretContract, hook: Option<Binary>) -> StdResult<CosmosMsg> {
+    handle_msg(
+        contract,
+        &ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount: None,
+            hook,
+            recipient: None,
+        },
+    )
+}
+
+// For claim operators, or for recipients that only want to claim part of what has accrued.
+pub fn claim_buttcoin_for_msg(
+    contract: &SecretContract,
+    recipient: HumanAddr,
+    amount: Option<Uint128>,
+    hook: Option<Binary>,
+) -> StdResult<CosmosMsg> {
+    handle_msg(
+        contract,
+        &ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount,
+            hook,
+            recipient: Some(recipient),
+        },
+    )
+}
+
+pub fn distribute_msg(contract: &SecretContract) -> StdResult<CosmosMsg> {
+    handle_msg(contract, &ButtcoinDistributorHandleMsg::Distribute {})
+}
+
+pub fn expire_rewards_msg(
+    contract: &SecretContract,
+    recipients: Vec<HumanAddr>,
+) -> StdResult<CosmosMsg> {
+    handle_msg(
+        contract,
+        &ButtcoinDistributorHandleMsg::ExpireRewards { recipients },
+    )
+}
+
+pub fn remove_claim_operator_msg(
+    contract: &SecretContract,
+    operator: HumanAddr,
+) -> StdResult<CosmosMsg> {
+    handle_msg(
+        contract,
+        &ButtcoinDistributorHandleMsg::RemoveClaimOperator { operator },
+    )
+}
+
+fn handle_msg(
+    contract: &SecretContract,
+    msg: &ButtcoinDistributorHandleMsg,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: contract.address.clone(),
+        callback_code_hash: contract.contract_hash.clone(),
+        msg: to_binary(msg)?,
+        send: vec![],
+    }))
+}
+
+// === QUERY ===
+
+pub fn claim_limits_query<Q: Querier>(
+    querier: &Q,
+    contract: &SecretContract,
+) -> StdResult<ClaimLimits> {
+    parse_claim_limits(query(
+        querier,
+        contract,
+        &ButtcoinDistributorQueryMsg::ClaimLimits {},
+    )?)
+}
+
+pub fn expiring_rewards_query<Q: Querier>(
+    querier: &Q,
+    contract: &SecretContract,
+    recipient: HumanAddr,
+) -> StdResult<Vec<AccrualBucket>> {
+    parse_expiring_rewards(query(
+        querier,
+        contract,
+        &ButtcoinDistributorQueryMsg::ExpiringRewards { recipient },
+    )?)
+}
+
+pub fn pending_query<Q: Querier>(
+    querier: &Q,
+    contract: &SecretContract,
+    block: u64,
+    recipient: Option<HumanAddr>,
+) -> StdResult<Uint128> {
+    parse_pending(query(
+        querier,
+        contract,
+        &ButtcoinDistributorQueryMsg::Pending { block, recipient },
+    )?)
+}
+
+pub fn recipients_query<Q: Querier>(
+    querier: &Q,
+    contract: &SecretContract,
+) -> StdResult<Vec<RecipientInfo>> {
+    parse_recipients(query(
+        querier,
+        contract,
+        &ButtcoinDistributorQueryMsg::Recipients {},
+    )?)
+}
+
+fn query<Q: Querier>(
+    querier: &Q,
+    contract: &SecretContract,
+    msg: &ButtcoinDistributorQueryMsg,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract.address.clone(),
+        callback_code_hash: contract.contract_hash.clone(),
+        msg: to_binary(msg)?,
+    }))
+}
+
+// === ANSWERS ===
+
+pub fn parse_claim_limits(answer: ButtcoinDistributorQueryAnswer) -> StdResult<ClaimLimits> {
+    match answer {
+        ButtcoinDistributorQueryAnswer::ClaimLimits {
+            epoch_length,
+            max_per_claim,
+            max_per_epoch,
+        } => Ok(ClaimLimits {
+            epoch_length,
+            max_per_claim,
+            max_per_epoch,
+        }),
+        _ => Err(unexpected_answer()),
+    }
+}
+
+pub fn parse_expiring_rewards(
+    answer: ButtcoinDistributorQueryAnswer,
+) -> StdResult<Vec<AccrualBucket>> {
+    match answer {
+        ButtcoinDistributorQueryAnswer::ExpiringRewards { accrual_buckets } => Ok(accrual_buckets),
+        _ => Err(unexpected_answer()),
+    }
+}
+
+pub fn parse_pending(answer: ButtcoinDistributorQueryAnswer) -> StdResult<Uint128> {
+    match answer {
+        ButtcoinDistributorQueryAnswer::Pending { amount } => Ok(amount),
+        _ => Err(unexpected_answer()),
+    }
+}
+
+pub fn parse_recipients(answer: ButtcoinDistributorQueryAnswer) -> StdResult<Vec<RecipientInfo>> {
+    match answer {
+        ButtcoinDistributorQueryAnswer::Recipients { recipients } => Ok(recipients),
+        _ => Err(unexpected_answer()),
+    }
+}
+
+fn unexpected_answer() -> StdError {
+    StdError::generic_err("Unexpected answer from the Buttcoin distributor.")
+}
+
+// === RECEIVE ===
+
+// The hook sent along with Buttcoin delivered to a yield optimizer with DeliveryMode::DepositButtcoin.
+pub fn deposit_buttcoin_hook(msg: &Binary) -> StdResult<Option<Binary>> {
+    match from_binary(msg)? {
+        YieldOptimizerReceiveMsg::DepositButtcoin { hook } => Ok(hook),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::YieldOptimizerDepositButtcoinHookMsg;
+
+    fn mock_distributor() -> SecretContract {
+        SecretContract {
+            address: HumanAddr::from("buttcoin-distributor-address"),
+            contract_hash: "buttcoin-distributor-contract-hash".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_claim_buttcoin_msg() {
+        let hook = to_binary(
+            &YieldOptimizerDepositButtcoinHookMsg::ContinueDepositAfterButtcoinClaimed {
+                depositer: HumanAddr::from("user"),
+                incentivized_token_amount: Uint128(5),
+            },
+        )
+        .unwrap();
+
+        // = When a claim message is built
+        // = * It executes ClaimButtcoin on the distributor with the hook
+        assert_eq!(
+            claim_buttcoin_msg(&mock_distributor(), Some(hook.clone())).unwrap(),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: mock_distributor().address,
+                callback_code_hash: mock_distributor().contract_hash,
+                msg: to_binary(&ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                    amount: None,
+                    hook: Some(hook.clone()),
+                    recipient: None,
+                })
+                .unwrap(),
+                send: vec![],
+            })
+        );
+
+        // = When the Buttcoin arrives at the yield optimizer
+        // = * It gives back the hook
+        let msg = to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin {
+            hook: Some(hook.clone()),
+        })
+        .unwrap();
+        assert_eq!(deposit_buttcoin_hook(&msg).unwrap(), Some(hook));
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        // = When the answer matches the query
+        // = * It returns its contents
+        assert_eq!(
+            parse_pending(ButtcoinDistributorQueryAnswer::Pending { amount: Uint128(5) }).unwrap(),
+            Uint128(5)
+        );
+        assert_eq!(
+            parse_claim_limits(ButtcoinDistributorQueryAnswer::ClaimLimits {
+                epoch_length: 10,
+                max_per_claim: Some(Uint128(1)),
+                max_per_epoch: None,
+            })
+            .unwrap(),
+            ClaimLimits {
+                epoch_length: 10,
+                max_per_claim: Some(Uint128(1)),
+                max_per_epoch: None,
+            }
+        );
+
+        // = When the answer is for a different query
+        // = * It raises an error
+        assert_eq!(
+            parse_recipients(ButtcoinDistributorQueryAnswer::Pending { amount: Uint128(5) })
+                .unwrap_err(),
+            StdError::generic_err("Unexpected answer from the Buttcoin distributor.")
+        );
+    }
+}
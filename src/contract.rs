@@ -1,39 +1,118 @@
+use crate::error::ContractError;
 use crate::msg::ButtcoinDistributorResponseStatus::Success;
 use crate::msg::{
     ButtcoinDistributorHandleAnswer, ButtcoinDistributorHandleMsg, ButtcoinDistributorQueryAnswer,
-    ButtcoinDistributorQueryMsg, InitMsg, YieldOptimizerReceiveMsg,
+    ButtcoinDistributorQueryMsg, ButtcoinDistributorReceiveMsg, InitMsg, PendingRecipientInfo,
+    RecipientInfo, YieldOptimizerDepositButtcoinHookMsg, YieldOptimizerReceiveMsg,
+};
+use crate::state::{
+    claim_limits_config, claim_limits_config_read, config, config_read, distribution_config,
+    distribution_config_read, fees_config, fees_config_read, gauge_config, gauge_config_read,
+    gauge_epochs, gauge_epochs_read, hook_validation_config, hook_validation_config_read,
+    keeper_config, keeper_config_read, multisig_config, multisig_config_read, pending_recipients,
+    pending_recipients_read, proposals, proposals_read, recipient_addresses,
+    recipient_addresses_read, recipients, recipients_read, replaced_recipients,
+    replaced_recipients_read, reward_expiry_config, reward_expiry_config_read, voters, voters_read,
+    AccrualBucket, CanonicalSecretContract, ClaimLimits, DeliveryMode, Distribution,
+    ExpiryDestination, Fees, Gauge, GaugeEpoch, GaugeWeight, HookValidation, Keeper, KeeperBounty,
+    Multisig, PendingRecipient, Proposal, Recipient, RewardExpiry, SecretContract, State,
+    VoteAllocation, Voter,
 };
-use crate::state::{config, config_read, SecretContract, State};
 use cosmwasm_std::{
-    to_binary, Api, Binary, Env, Extern, HandleResponse, InitResponse, Querier, StdError,
-    StdResult, Storage, Uint128,
+    from_binary, log, to_binary, Api, Binary, CanonicalAddr, CosmosMsg, Env, Extern,
+    HandleResponse, HumanAddr, InitResponse, Querier, StdError, StdResult, Storage, Uint128,
+    WasmMsg,
 };
 use secret_toolkit::snip20;
+use serde::de::IgnoredAny;
+use std::collections::BTreeMap;
+
+// Scales acc_buttcoin_per_weight so that small releases spread over a large weight aren't rounded
+// away.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000_000_000;
+const MAX_BASIS_POINTS: u16 = 10_000;
+// Recipient weights add up to at most MAX_TOTAL_WEIGHT. Gauge weights are the recipients' shares of
+// the vote tally in parts of it, so that they stay small however much Buttcoin is locked.
+pub const MAX_TOTAL_WEIGHT: u128 = 1_000_000;
+// Emission is scaled by REWARD_PRECISION and multiplied by weights of up to MAX_TOTAL_WEIGHT, so
+// anything more could overflow the reward math.
+pub const MAX_TOTAL_EMISSION: u128 = u128::MAX / REWARD_PRECISION / MAX_TOTAL_WEIGHT - 1;
+// Keeps Distribute within the gas limit until the admin configures it.
+const DEFAULT_MAX_RECIPIENTS_PER_DISTRIBUTE: u64 = 10;
+// Caps the accrual buckets added for a recipient at once, so that the gas used doesn't grow with
+// expiry_blocks / bucket_length.
+const MAX_NEW_ACCRUAL_BUCKETS: u64 = 10;
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
+    let buttcoin = canonicalize_contract(&deps.api, &msg.buttcoin)?;
+    let release_per_block_denominator = validate_schedule(
+        msg.starting_block,
+        msg.end_block,
+        msg.release_per_block,
+        msg.release_per_block_denominator,
+    )?;
+    // Defaults to what the initial schedule releases
+    let max_total_emission = match msg.max_total_emission {
+        Some(max_total_emission) if max_total_emission.is_zero() => {
+            return Err(ContractError::MustBePositive {
+                field: "Max total emission",
+            }
+            .into())
+        }
+        Some(max_total_emission) => max_total_emission,
+        None => Uint128(
+            checked_mul(
+                (msg.end_block - msg.starting_block) as u128,
+                msg.release_per_block.u128(),
+                "max total emission",
+            )? / release_per_block_denominator as u128,
+        ),
+    };
+    validate_max_total_emission(max_total_emission)?;
     let state = State {
-        buttcoin: msg.buttcoin.clone(),
+        acc_buttcoin_per_weight: Uint128(0),
+        admin: env.message.sender,
+        buttcoin,
         end_block: msg.end_block,
+        frozen: false,
+        gauge_voting: false,
         last_update_block: msg.starting_block,
+        max_total_emission,
         receivable_smart_contract: None,
         release_per_block: msg.release_per_block,
+        release_per_block_denominator,
+        reward_remainder: Uint128(0),
+        schedule_checkpoint_block: msg.starting_block,
         starting_block: msg.starting_block,
+        total_emitted: Uint128(0),
+        total_funded: Uint128(0),
+        total_weight: Uint128(0),
+        unallocated_rewards: Uint128(0),
         viewing_key: msg.viewing_key.clone(),
     };
 
     config(&mut deps.storage).save(&state)?;
 
-    let messages = vec![snip20::set_viewing_key_msg(
-        msg.viewing_key,
-        None,
-        1,
-        msg.buttcoin.contract_hash,
-        msg.buttcoin.address,
-    )?];
+    let messages = vec![
+        snip20::register_receive_msg(
+            env.contract_code_hash,
+            None,
+            1,
+            msg.buttcoin.contract_hash.clone(),
+            msg.buttcoin.address.clone(),
+        )?,
+        snip20::set_viewing_key_msg(
+            msg.viewing_key,
+            None,
+            1,
+            msg.buttcoin.contract_hash,
+            msg.buttcoin.address,
+        )?,
+    ];
 
     Ok(InitResponse {
         messages,
@@ -47,10 +126,48 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: ButtcoinDistributorHandleMsg,
 ) -> StdResult<HandleResponse> {
     match msg {
-        ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
-            receivable_smart_contract,
-        } => set_receivable_smart_contract(deps, env, receivable_smart_contract),
-        ButtcoinDistributorHandleMsg::ClaimButtcoin { hook } => claim_buttcoin(deps, env, hook),
+        ButtcoinDistributorHandleMsg::AcceptRecipient { contract_hash } => {
+            accept_recipient(deps, env, contract_hash)
+        }
+        ButtcoinDistributorHandleMsg::AddClaimOperator { operator } => {
+            add_claim_operator(deps, env, operator)
+        }
+        ButtcoinDistributorHandleMsg::ApproveProposal { id } => approve_proposal(deps, env, id),
+        ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount,
+            hook,
+            recipient,
+        } => claim_buttcoin(deps, env, amount, hook, recipient),
+        ButtcoinDistributorHandleMsg::Distribute {} => distribute(deps, env),
+        ButtcoinDistributorHandleMsg::ExpireRewards { recipients } => {
+            expire_rewards(deps, env, recipients)
+        }
+        ButtcoinDistributorHandleMsg::Receive {
+            from, amount, msg, ..
+        } => receive(deps, env, from, amount, msg),
+        ButtcoinDistributorHandleMsg::RemoveClaimOperator { operator } => {
+            remove_claim_operator(deps, env, operator)
+        }
+        ButtcoinDistributorHandleMsg::UnlockButtcoin { amount } => {
+            unlock_buttcoin(deps, env, amount)
+        }
+        ButtcoinDistributorHandleMsg::Vote { allocations } => vote(deps, env, allocations),
+        ButtcoinDistributorHandleMsg::AddRecipient { .. }
+        | ButtcoinDistributorHandleMsg::ChangeAdmin { .. }
+        | ButtcoinDistributorHandleMsg::EnableGaugeVoting { .. }
+        | ButtcoinDistributorHandleMsg::RenounceAdmin {}
+        | ButtcoinDistributorHandleMsg::ReplaceRecipient { .. }
+        | ButtcoinDistributorHandleMsg::SetClaimLimits { .. }
+        | ButtcoinDistributorHandleMsg::SetDistribution { .. }
+        | ButtcoinDistributorHandleMsg::SetFees { .. }
+        | ButtcoinDistributorHandleMsg::SetHookValidation { .. }
+        | ButtcoinDistributorHandleMsg::SetKeeper { .. }
+        | ButtcoinDistributorHandleMsg::SetMultisig { .. }
+        | ButtcoinDistributorHandleMsg::SetReceivableSmartContract { .. }
+        | ButtcoinDistributorHandleMsg::SetRecipientHookVariants { .. }
+        | ButtcoinDistributorHandleMsg::SetRecipientWeight { .. }
+        | ButtcoinDistributorHandleMsg::SetRewardExpiry { .. }
+        | ButtcoinDistributorHandleMsg::SetSchedule { .. } => submit_privileged(deps, env, msg),
     }
 }
 
@@ -59,242 +176,6865 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
     msg: ButtcoinDistributorQueryMsg,
 ) -> StdResult<Binary> {
     match msg {
+        ButtcoinDistributorQueryMsg::ClaimLimits {} => to_binary(&query_claim_limits(deps)?),
         ButtcoinDistributorQueryMsg::Config {} => to_binary(&query_config(deps)?),
-        ButtcoinDistributorQueryMsg::Pending { block } => {
-            to_binary(&query_pending_rewards(deps, block)?)
+        ButtcoinDistributorQueryMsg::ExpiringRewards { recipient } => {
+            to_binary(&query_expiring_rewards(deps, recipient)?)
+        }
+        ButtcoinDistributorQueryMsg::FeeStats {} => to_binary(&query_fee_stats(deps)?),
+        ButtcoinDistributorQueryMsg::GaugeWeights { epoch } => {
+            to_binary(&query_gauge_weights(deps, epoch)?)
+        }
+        ButtcoinDistributorQueryMsg::Keeper {} => to_binary(&query_keeper(deps)?),
+        ButtcoinDistributorQueryMsg::Pending { block, recipient } => {
+            to_binary(&query_pending_rewards(deps, block, recipient)?)
         }
+        ButtcoinDistributorQueryMsg::PendingRecipients {} => {
+            to_binary(&query_pending_recipients(deps)?)
+        }
+        ButtcoinDistributorQueryMsg::Proposal { id } => to_binary(&query_proposal(deps, id)?),
+        ButtcoinDistributorQueryMsg::Proposals {} => to_binary(&query_proposals(deps)?),
+        ButtcoinDistributorQueryMsg::Recipients {} => to_binary(&query_recipients(deps)?),
+        ButtcoinDistributorQueryMsg::RewardExpiry {} => to_binary(&query_reward_expiry(deps)?),
+        ButtcoinDistributorQueryMsg::Votes { voter } => to_binary(&query_votes(deps, voter)?),
     }
 }
 
+fn query_claim_limits<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    let claim_limits = load_claim_limits(&deps.storage)?;
+
+    Ok(ButtcoinDistributorQueryAnswer::ClaimLimits {
+        epoch_length: claim_limits.epoch_length,
+        max_per_claim: claim_limits.max_per_claim,
+        max_per_epoch: claim_limits.max_per_epoch,
+    })
+}
+
 fn query_config<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
 ) -> StdResult<ButtcoinDistributorQueryAnswer> {
     let state: State = config_read(&deps.storage).load()?;
+    let emission_headroom = emission_headroom(&state);
 
     Ok(ButtcoinDistributorQueryAnswer::Config {
-        buttcoin: state.buttcoin,
+        admin: state.admin,
+        buttcoin: humanize_contract(&deps.api, &state.buttcoin)?,
+        emission_headroom: Uint128(emission_headroom),
         end_block: state.end_block,
+        frozen: state.frozen,
         last_update_block: state.last_update_block,
-        receivable_smart_contract: state.receivable_smart_contract,
+        max_total_emission: state.max_total_emission,
+        receivable_smart_contract: state
+            .receivable_smart_contract
+            .as_ref()
+            .map(|receivable_smart_contract| {
+                humanize_contract(&deps.api, receivable_smart_contract)
+            })
+            .transpose()?,
         release_per_block: state.release_per_block,
+        release_per_block_denominator: state.release_per_block_denominator,
         starting_block: state.starting_block,
+        total_funded: state.total_funded,
         viewing_key: state.viewing_key,
     })
 }
 
+// Only shows what has been put up for expiry so far.
+fn query_expiring_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    recipient: HumanAddr,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    match recipients_read(&deps.storage).may_load(recipient.0.as_bytes())? {
+        Some(recipient) => Ok(ButtcoinDistributorQueryAnswer::ExpiringRewards {
+            accrual_buckets: recipient.accrual_buckets,
+        }),
+        None => Err(ContractError::NotRecipient { address: recipient }.into()),
+    }
+}
+
+fn query_fee_stats<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    let fees = load_fees(&deps.storage)?;
+
+    Ok(ButtcoinDistributorQueryAnswer::FeeStats {
+        burn_basis_points: fees.burn_basis_points,
+        total_burned: fees.total_burned,
+        total_treasury: fees.total_treasury,
+        treasury: fees.treasury,
+        treasury_basis_points: fees.treasury_basis_points,
+    })
+}
+
+// Weights of epochs that haven't been reached yet are projected from the live tally.
+fn query_gauge_weights<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    epoch: u64,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    let gauge = load_gauge(&deps.storage)?;
+    if epoch > gauge.epoch && tally_total(&gauge.tally) > 0 {
+        return Ok(ButtcoinDistributorQueryAnswer::GaugeWeights {
+            epoch,
+            weights: gauge_weights(&gauge.tally),
+        });
+    }
+
+    let mut snapshot =
+        gauge_epochs_read(&deps.storage).load(&gauge.latest_snapshot_epoch.to_be_bytes())?;
+    while snapshot.epoch > epoch {
+        match snapshot.previous_snapshot_epoch {
+            Some(previous_snapshot_epoch) => {
+                snapshot =
+                    gauge_epochs_read(&deps.storage).load(&previous_snapshot_epoch.to_be_bytes())?
+            }
+            None => return Err(ContractError::GaugeVotingNotEnabledInEpoch.into()),
+        }
+    }
+
+    Ok(ButtcoinDistributorQueryAnswer::GaugeWeights {
+        epoch,
+        weights: snapshot.weights,
+    })
+}
+
+fn query_keeper<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    Ok(match keeper_config_read(&deps.storage).may_load()? {
+        Some(keeper) => ButtcoinDistributorQueryAnswer::Keeper {
+            bounty: Some(keeper.bounty),
+            last_paid_block: keeper.last_paid_block,
+            min_interval: keeper.min_interval,
+            paid_cranks: keeper.paid_cranks,
+            total_paid: keeper.total_paid,
+        },
+        None => ButtcoinDistributorQueryAnswer::Keeper {
+            bounty: None,
+            last_paid_block: None,
+            min_interval: 0,
+            paid_cranks: 0,
+            total_paid: Uint128(0),
+        },
+    })
+}
+
+// Without a recipient this is for the receivable smart contract, or for the Buttcoin released while
+// nobody had any weight when there is no receivable smart contract.
 fn query_pending_rewards<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     block: u64,
+    recipient: Option<HumanAddr>,
 ) -> StdResult<ButtcoinDistributorQueryAnswer> {
-    let state = config_read(&deps.storage).load()?;
-    let amount = get_receivable_contract_rewards(block, state);
+    let mut state = config_read(&deps.storage).load()?;
+    let address = match recipient {
+        Some(address) => Some(address),
+        None => state
+            .receivable_smart_contract
+            .as_ref()
+            .map(|receivable_smart_contract| {
+                deps.api.human_address(&receivable_smart_contract.address)
+            })
+            .transpose()?,
+    };
+    let amount = match address {
+        Some(address) => match recipients_read(&deps.storage).may_load(address.0.as_bytes())? {
+            Some(recipient) => {
+                get_pending_rewards(&deps.storage, state, &address, recipient, block)?
+            }
+            // Nothing is earned until the registration is accepted
+            None if is_pending_recipient(deps, &address)? => 0,
+            None => return Err(ContractError::NotRecipient { address }.into()),
+        },
+        None => {
+            update_pool(&mut state, block)?;
+            state.unallocated_rewards.u128()
+        }
+    };
 
     Ok(ButtcoinDistributorQueryAnswer::Pending {
         amount: Uint128(amount),
     })
 }
 
-fn get_receivable_contract_rewards(block: u64, state: State) -> u128 {
+fn query_pending_recipients<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    let mut pending_recipients = vec![];
+    for pending in pending_recipients_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+    {
+        pending_recipients.push(PendingRecipientInfo {
+            contract: humanize_contract(&deps.api, &pending.contract)?,
+            delivery_mode: pending.delivery_mode,
+            replaces: pending.replaces,
+            weight: pending.weight,
+        });
+    }
+
+    Ok(ButtcoinDistributorQueryAnswer::PendingRecipients { pending_recipients })
+}
+
+fn query_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    id: u64,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    let proposal = match proposals_read(&deps.storage).may_load(&id.to_be_bytes())? {
+        Some(proposal) => proposal,
+        None => return Err(ContractError::ProposalNotFound { id }.into()),
+    };
+
+    Ok(ButtcoinDistributorQueryAnswer::Proposal { proposal })
+}
+
+// Only proposals that are still waiting for approvals are listed.
+// Queries don't know the current height, so expired ones are left for the caller to filter
+// by expires_at_block.
+fn query_proposals<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    let mut proposals = vec![];
+    if let Some(multisig) = multisig_config_read(&deps.storage).may_load()? {
+        for id in multisig.pending_proposal_ids {
+            proposals.push(proposals_read(&deps.storage).load(&id.to_be_bytes())?);
+        }
+    }
+
+    Ok(ButtcoinDistributorQueryAnswer::Proposals { proposals })
+}
+
+fn query_recipients<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    let mut recipients = vec![];
+    for address in recipient_addresses_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+    {
+        let recipient = recipients_read(&deps.storage).load(address.0.as_bytes())?;
+        recipients.push(RecipientInfo {
+            accrual_buckets: recipient.accrual_buckets,
+            accrued: recipient.accrued,
+            bucketed_until_block: recipient.bucketed_until_block,
+            claim_epoch: recipient.claim_epoch,
+            claim_operators: recipient.claim_operators,
+            claimed_in_epoch: recipient.claimed_in_epoch,
+            contract: humanize_contract(&deps.api, &recipient.contract)?,
+            delivery_mode: recipient.delivery_mode,
+            hook_variants: recipient.hook_variants,
+            reward_debt: recipient.reward_debt,
+            unexpiring: recipient.unexpiring,
+            weight: recipient.weight,
+        });
+    }
+
+    Ok(ButtcoinDistributorQueryAnswer::Recipients { recipients })
+}
+
+fn query_reward_expiry<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    Ok(match reward_expiry_config_read(&deps.storage).may_load()? {
+        Some(reward_expiry) => ButtcoinDistributorQueryAnswer::RewardExpiry {
+            bucket_length: reward_expiry.bucket_length,
+            destination: Some(reward_expiry.destination),
+            expiry_blocks: reward_expiry.expiry_blocks,
+            total_expired: reward_expiry.total_expired,
+        },
+        None => ButtcoinDistributorQueryAnswer::RewardExpiry {
+            bucket_length: 0,
+            destination: None,
+            expiry_blocks: None,
+            total_expired: Uint128(0),
+        },
+    })
+}
+
+fn query_votes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    voter: HumanAddr,
+) -> StdResult<ButtcoinDistributorQueryAnswer> {
+    let mut voter = load_voter(&deps.storage, &voter)?;
+    if let Some(gauge) = gauge_config_read(&deps.storage).may_load()? {
+        mark_voted_epoch(&mut voter, gauge.epoch);
+    }
+
+    Ok(ButtcoinDistributorQueryAnswer::Votes {
+        allocations: voter.allocations,
+        locked: voter.locked,
+        locked_in_epoch: voter.locked_in_epoch,
+    })
+}
+
+// === ADDRESSES ===
+
+// Rejects addresses the chain can't canonicalize.
+fn canonicalize_address<A: Api>(api: &A, address: &HumanAddr) -> StdResult<CanonicalAddr> {
+    match api.canonical_address(address) {
+        Ok(address) => Ok(address),
+        Err(_) => Err(ContractError::InvalidAddress {
+            address: address.clone(),
+        }
+        .into()),
+    }
+}
+
+// Addresses that are kept human readable are stored the way the chain writes them back, so that
+// they compare equal to the senders they stand for.
+fn validate_address<A: Api>(api: &A, address: &HumanAddr) -> StdResult<HumanAddr> {
+    api.human_address(&canonicalize_address(api, address)?)
+}
+
+// Rejects addresses the chain can't canonicalize and contract hashes that aren't a hex SHA-256.
+fn canonicalize_contract<A: Api>(
+    api: &A,
+    contract: &SecretContract,
+) -> StdResult<CanonicalSecretContract> {
+    let address = canonicalize_address(api, &contract.address)?;
+    if !matches!(hex::decode(&contract.contract_hash), Ok(bytes) if bytes.len() == 32) {
+        return Err(ContractError::InvalidContractHash {
+            contract_hash: contract.contract_hash.clone(),
+        }
+        .into());
+    }
+
+    Ok(CanonicalSecretContract {
+        address,
+        contract_hash: contract.contract_hash.clone(),
+    })
+}
+
+fn humanize_contract<A: Api>(
+    api: &A,
+    contract: &CanonicalSecretContract,
+) -> StdResult<SecretContract> {
+    Ok(SecretContract {
+        address: api.human_address(&contract.address)?,
+        contract_hash: contract.contract_hash.clone(),
+    })
+}
+
+// === REWARDS ===
+
+// Reward math is checked rather than relying on overflow-checks, so that a schedule or weight that
+// is too large for it fails with an error naming what overflowed instead of a panic.
+fn checked_add(a: u128, b: u128, value: &'static str) -> StdResult<u128> {
+    a.checked_add(b)
+        .ok_or_else(|| ContractError::Overflow { value }.into())
+}
+
+fn checked_mul(a: u128, b: u128, value: &'static str) -> StdResult<u128> {
+    a.checked_mul(b)
+        .ok_or_else(|| ContractError::Overflow { value }.into())
+}
+
+fn checked_sub(a: u128, b: u128, value: &'static str) -> StdResult<u128> {
+    a.checked_sub(b)
+        .ok_or_else(|| ContractError::Overflow { value }.into())
+}
+
+// Only funded Buttcoin is released once gauge voting is enabled, so that locked Buttcoin is never
+// paid out as rewards.
+fn emission_headroom(state: &State) -> u128 {
+    let headroom = state
+        .max_total_emission
+        .u128()
+        .saturating_sub(state.total_emitted.u128());
+    if state.gauge_voting {
+        headroom.min(
+            state
+                .total_funded
+                .u128()
+                .saturating_sub(state.total_emitted.u128()),
+        )
+    } else {
+        headroom
+    }
+}
+
+// Shared by init and SetSchedule, which returns the denominator to use.
+// The release up to end_block has to fit, so that the schedule can't overflow before it is reached.
+fn validate_schedule(
+    starting_block: u64,
+    end_block: u64,
+    release_per_block: Uint128,
+    release_per_block_denominator: Option<u64>,
+) -> StdResult<u64> {
+    if end_block <= starting_block {
+        return Err(ContractError::InvalidSchedule {
+            reason: "End block must be greater than the starting block.",
+        }
+        .into());
+    }
+    if release_per_block.is_zero() {
+        return Err(ContractError::MustBePositive {
+            field: "Release per block",
+        }
+        .into());
+    }
+    let release_per_block_denominator = release_per_block_denominator.unwrap_or(1);
+    if release_per_block_denominator == 0 {
+        return Err(ContractError::MustBePositive {
+            field: "Release per block denominator",
+        }
+        .into());
+    }
+    if ((end_block - starting_block) as u128)
+        .checked_mul(release_per_block.u128())
+        .is_none()
+    {
+        return Err(ContractError::InvalidSchedule {
+            reason: "Release per block is too large for the length of the schedule.",
+        }
+        .into());
+    }
+
+    Ok(release_per_block_denominator)
+}
+
+fn validate_max_total_emission(max_total_emission: Uint128) -> StdResult<()> {
+    if max_total_emission.u128() > MAX_TOTAL_EMISSION {
+        return Err(ContractError::InvalidSchedule {
+            reason: "Max total emission is too large for the reward math.",
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+// Keeps the total weight within MAX_TOTAL_WEIGHT when a recipient's weight changes from old_weight
+// to weight.
+fn validate_total_weight(state: &State, old_weight: Uint128, weight: Uint128) -> StdResult<()> {
+    let other_weight = state.total_weight.u128().saturating_sub(old_weight.u128());
+    if weight.u128() > MAX_TOTAL_WEIGHT.saturating_sub(other_weight) {
+        return Err(ContractError::TooMuchWeight.into());
+    }
+
+    Ok(())
+}
+
+// Amount released by the current schedule up to block.
+// It is rounded down as a whole rather than per block, so that fractional rates don't lose a
+// little every block.
+fn get_scheduled_rewards(block: u64, state: &State) -> StdResult<u128> {
+    let block = if block > state.end_block {
+        state.end_block
+    } else {
+        block
+    };
+
+    if block > state.schedule_checkpoint_block {
+        Ok(checked_mul(
+            (block - state.schedule_checkpoint_block) as u128,
+            state.release_per_block.u128(),
+            "scheduled rewards",
+        )? / state.release_per_block_denominator as u128)
+    } else {
+        Ok(0)
+    }
+}
+
+// Amount released by the schedule between the last update and block, which stops once
+// max_total_emission has been reached.
+fn get_released_rewards(block: u64, state: &State) -> StdResult<u128> {
     if block > state.last_update_block {
-        let block = if block > state.end_block {
-            state.end_block
-        } else {
-            block
-        };
+        Ok(checked_sub(
+            get_scheduled_rewards(block, state)?,
+            get_scheduled_rewards(state.last_update_block, state)?,
+            "released rewards",
+        )?
+        .min(emission_headroom(state)))
+    } else {
+        Ok(0)
+    }
+}
 
-        (block - state.last_update_block) as u128 * state.release_per_block.u128()
+// Spreads everything released up to block over the current weights.
+// What can't be divided evenly is added to reward_remainder instead of being lost.
+fn update_pool(state: &mut State, block: u64) -> StdResult<()> {
+    let released_rewards = get_released_rewards(block, state)?;
+    state.total_emitted = Uint128(checked_add(
+        state.total_emitted.u128(),
+        released_rewards,
+        "total emitted",
+    )?);
+    let rewards = checked_add(
+        state.unallocated_rewards.u128(),
+        released_rewards,
+        "unallocated rewards",
+    )?;
+    if block > state.last_update_block {
+        state.last_update_block = block;
+    }
+    if state.total_weight.is_zero() {
+        state.unallocated_rewards = Uint128(rewards);
     } else {
-        0
+        let scaled_rewards = checked_mul(rewards, REWARD_PRECISION, "scaled rewards")?;
+        state.acc_buttcoin_per_weight = Uint128(checked_add(
+            state.acc_buttcoin_per_weight.u128(),
+            scaled_rewards / state.total_weight.u128(),
+            "Buttcoin per weight",
+        )?);
+        state.reward_remainder = Uint128(checked_add(
+            state.reward_remainder.u128(),
+            scaled_rewards % state.total_weight.u128(),
+            "reward remainder",
+        )?);
+        state.unallocated_rewards = Uint128(0);
     }
+
+    Ok(())
 }
 
-fn claim_buttcoin<S: Storage, A: Api, Q: Querier>(
-    deps: &mut Extern<S, A, Q>,
-    env: Env,
-    hook: Option<Binary>,
-) -> StdResult<HandleResponse> {
-    let mut state = config_read(&deps.storage).load()?;
-    let mut rewards = 0;
+// Brings the pool up to block.
+// Gauge weights only need to be applied at the first epoch boundary crossed since the last update,
+// as nobody could have voted in between.
+fn update_rewards<S: Storage>(storage: &mut S, state: &mut State, block: u64) -> StdResult<()> {
+    if let Some(mut gauge) = gauge_config_read(storage).may_load()? {
+        let epoch = gauge_epoch(&gauge, state, block);
+        if epoch > gauge.epoch {
+            let boundary_epoch = gauge.epoch + 1;
+            update_pool(state, epoch_starting_block(&gauge, state, boundary_epoch))?;
+            if tally_total(&gauge.tally) > 0 {
+                let weights = apply_gauge_weights(storage, state, &gauge.tally)?;
+                gauge_epochs(storage).save(
+                    &boundary_epoch.to_be_bytes(),
+                    &GaugeEpoch {
+                        epoch: boundary_epoch,
+                        previous_snapshot_epoch: Some(gauge.latest_snapshot_epoch),
+                        weights,
+                    },
+                )?;
+                gauge.latest_snapshot_epoch = boundary_epoch;
+            }
+            gauge.epoch = epoch;
+            gauge_config(storage).save(&gauge)?;
+        }
+    }
+    update_pool(state, block)?;
 
-    if state.receivable_smart_contract.is_some() {
-        if state.clone().receivable_smart_contract.unwrap().address == env.message.sender {
-            rewards = get_receivable_contract_rewards(env.block.height, state.clone());
-            state.last_update_block = env.block.height;
-            config(&mut deps.storage).save(&state)?;
+    Ok(())
+}
+
+// The fraction of a Buttcoin that can't be paid out is added to reward_remainder, and whoever settles
+// once that adds up to whole Buttcoin gets them, so that everything released is paid out in the end.
+fn settle_recipient(state: &mut State, recipient: &mut Recipient) -> StdResult<()> {
+    let earned = checked_mul(
+        recipient.weight.u128(),
+        state.acc_buttcoin_per_weight.u128(),
+        "earned rewards",
+    )?;
+    let scaled_rewards = checked_sub(earned, recipient.reward_debt.u128(), "earned rewards")?;
+    let leftovers = checked_add(
+        state.reward_remainder.u128(),
+        scaled_rewards % REWARD_PRECISION,
+        "reward remainder",
+    )?;
+    recipient.accrued = Uint128(checked_add(
+        recipient.accrued.u128(),
+        scaled_rewards / REWARD_PRECISION + leftovers / REWARD_PRECISION,
+        "accrued rewards",
+    )?);
+    recipient.reward_debt = Uint128(earned);
+    state.reward_remainder = Uint128(leftovers % REWARD_PRECISION);
+
+    Ok(())
+}
+
+// The pool has to be up to date before a weight changes.
+fn change_recipient_weight(
+    state: &mut State,
+    recipient: &mut Recipient,
+    weight: Uint128,
+) -> StdResult<()> {
+    settle_recipient(state, recipient)?;
+    state.total_weight = Uint128(checked_add(
+        checked_sub(
+            state.total_weight.u128(),
+            recipient.weight.u128(),
+            "total weight",
+        )?,
+        weight.u128(),
+        "total weight",
+    )?);
+    recipient.weight = weight;
+    recipient.reward_debt = Uint128(checked_mul(
+        weight.u128(),
+        state.acc_buttcoin_per_weight.u128(),
+        "earned rewards",
+    )?);
+
+    Ok(())
+}
+
+// Read only version of update_rewards for a single recipient.
+fn get_pending_rewards<S: Storage>(
+    storage: &S,
+    mut state: State,
+    address: &HumanAddr,
+    mut recipient: Recipient,
+    block: u64,
+) -> StdResult<u128> {
+    if let Some(gauge) = gauge_config_read(storage).may_load()? {
+        if gauge_epoch(&gauge, &state, block) > gauge.epoch && tally_total(&gauge.tally) > 0 {
+            let boundary_block = epoch_starting_block(&gauge, &state, gauge.epoch + 1);
+            update_pool(&mut state, boundary_block)?;
+            let weights = gauge_weights(&gauge.tally);
+            change_recipient_weight(&mut state, &mut recipient, find_weight(&weights, address))?;
+            state.total_weight = Uint128(tally_total(&weights));
         }
     }
+    update_pool(&mut state, block)?;
+    settle_recipient(&mut state, &mut recipient)?;
 
-    Ok(HandleResponse {
-        messages: vec![snip20::send_msg(
-            env.message.sender.clone(),
-            Uint128(rewards),
-            Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin {
-                hook: hook,
-            })?),
-            None,
-            1,
-            state.buttcoin.contract_hash.clone(),
-            state.buttcoin.address,
-        )?],
-        log: vec![],
-        data: Some(to_binary(
-            &ButtcoinDistributorHandleAnswer::ClaimButtcoin { status: Success },
-        )?),
-    })
+    Ok(recipient.accrued.u128())
 }
 
-fn set_receivable_smart_contract<S: Storage, A: Api, Q: Querier>(
+// The pool has to be up to date before registering.
+fn register_recipient<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
-    receivable_smart_contract: SecretContract,
-) -> StdResult<HandleResponse> {
-    let mut state = config_read(&deps.storage).load()?;
-    if state.receivable_smart_contract.is_some() {
-        return Err(StdError::generic_err(format!(
-            "Receivable smart contract can only be set once!"
-        )));
+    state: &mut State,
+    contract: CanonicalSecretContract,
+    weight: Uint128,
+    delivery_mode: DeliveryMode,
+) -> StdResult<()> {
+    let address = deps.api.human_address(&contract.address)?;
+    assert_can_register(deps, &address)?;
+    validate_total_weight(state, Uint128(0), weight)?;
+
+    let mut recipient = Recipient {
+        accrual_buckets: vec![],
+        accrued: Uint128(0),
+        bucketed_until_block: state.last_update_block,
+        claim_epoch: 0,
+        claim_operators: vec![],
+        claimed_in_epoch: Uint128(0),
+        contract,
+        delivery_mode,
+        hook_variants: None,
+        reward_debt: Uint128(0),
+        unexpiring: Uint128(0),
+        weight: Uint128(0),
+    };
+    change_recipient_weight(state, &mut recipient, weight)?;
+    recipients(&mut deps.storage).save(address.0.as_bytes(), &recipient)?;
+    let mut addresses = recipient_addresses_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    addresses.push(address);
+    recipient_addresses(&mut deps.storage).save(&addresses)?;
+
+    Ok(())
+}
+
+// The recipient does not earn anything until the contract calls AcceptRecipient.
+fn register_pending_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    contract: SecretContract,
+    weight: Uint128,
+    delivery_mode: DeliveryMode,
+    replaces: Option<HumanAddr>,
+) -> StdResult<()> {
+    let canonical_contract = canonicalize_contract(&deps.api, &contract)?;
+    assert_can_register(deps, &contract.address)?;
+    let mut pending = pending_recipients_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    pending.push(PendingRecipient {
+        contract: canonical_contract,
+        delivery_mode,
+        replaces,
+        weight,
+    });
+    pending_recipients(&mut deps.storage).save(&pending)?;
+
+    Ok(())
+}
+
+fn assert_can_register<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+) -> StdResult<()> {
+    if recipients_read(&deps.storage)
+        .may_load(address.0.as_bytes())?
+        .is_some()
+    {
+        return Err(ContractError::AlreadyRegistered.into());
+    }
+    if replaced_recipients_read(&deps.storage)
+        .may_load(address.0.as_bytes())?
+        .is_some()
+    {
+        return Err(ContractError::RecipientReplaced.into());
+    }
+    if is_pending_recipient(deps, address)? {
+        return Err(ContractError::AlreadyPending.into());
     }
 
-    state.receivable_smart_contract = Some(receivable_smart_contract);
-    config(&mut deps.storage).save(&state)?;
+    Ok(())
+}
 
-    Ok(HandleResponse {
-        messages: vec![],
-        log: vec![],
-        data: Some(to_binary(
-            &ButtcoinDistributorHandleAnswer::SetReceivableSmartContract { status: Success },
-        )?),
-    })
+// An address that can't be canonicalized can't have been registered.
+fn is_pending_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+) -> StdResult<bool> {
+    let address = match deps.api.canonical_address(address) {
+        Ok(address) => address,
+        Err(_) => return Ok(false),
+    };
+
+    Ok(pending_recipients_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .iter()
+        .any(|pending| pending.contract.address == address))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::msg::YieldOptimizerDepositButtcoinHookMsg;
-    use crate::state::SecretContract;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
-    use cosmwasm_std::{from_binary, HumanAddr};
+// === GAUGE ===
 
-    // === CONSTANTS ===
-    pub const MOCK_SMART_CONTRACT_INITIALIZER: &str = "smart_contract_initializer";
+fn load_gauge<S: Storage>(storage: &S) -> StdResult<Gauge> {
+    match gauge_config_read(storage).may_load()? {
+        Some(gauge) => Ok(gauge),
+        None => Err(ContractError::GaugeVotingNotEnabled.into()),
+    }
+}
 
-    // === HELPERS ===
-    fn init_helper() -> (
-        StdResult<InitResponse>,
-        Extern<MockStorage, MockApi, MockQuerier>,
-    ) {
-        let env = mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]);
-        let mut deps = mock_dependencies(20, &[]);
-        let msg = InitMsg {
-            buttcoin: mock_buttcoin(),
-            end_block: 123,
-            release_per_block: Uint128(34),
-            starting_block: 122,
-            viewing_key: mock_viewing_key(),
-        };
-        (init(&mut deps, env.clone(), msg), deps)
+fn gauge_epoch(gauge: &Gauge, state: &State, block: u64) -> u64 {
+    if block > state.starting_block {
+        (block - state.starting_block) / gauge.epoch_length
+    } else {
+        0
     }
+}
 
-    fn mock_buttcoin() -> SecretContract {
-        SecretContract {
-            address: HumanAddr::from("buttcoincontractaddress"),
-            contract_hash: "buttcoincontracthash".to_string(),
-        }
+// Saturates, as a block that doesn't fit in a u64 is never reached.
+fn epoch_starting_block(gauge: &Gauge, state: &State, epoch: u64) -> u64 {
+    state
+        .starting_block
+        .saturating_add(epoch.saturating_mul(gauge.epoch_length))
+}
+
+fn tally_total(tally: &[GaugeWeight]) -> u128 {
+    tally
+        .iter()
+        .map(|gauge_weight| gauge_weight.weight.u128())
+        .sum()
+}
+
+// The tally can only be empty when its total is zero, so that is never divided by.
+fn gauge_weights(tally: &[GaugeWeight]) -> Vec<GaugeWeight> {
+    let total = tally_total(tally);
+    // Both sides are scaled down first when the total is too large to be multiplied
+    let scale = total / (u128::MAX / MAX_TOTAL_WEIGHT) + 1;
+    tally
+        .iter()
+        .map(|gauge_weight| GaugeWeight {
+            recipient: gauge_weight.recipient.clone(),
+            weight: Uint128(
+                gauge_weight.weight.u128() / scale * MAX_TOTAL_WEIGHT / (total / scale),
+            ),
+        })
+        .collect()
+}
+
+fn find_weight(weights: &[GaugeWeight], recipient: &HumanAddr) -> Uint128 {
+    match weights
+        .iter()
+        .find(|gauge_weight| gauge_weight.recipient == *recipient)
+    {
+        Some(gauge_weight) => gauge_weight.weight,
+        None => Uint128(0),
+    }
+}
+
+// Votes for replaced recipients count for the recipients that replaced them.
+fn load_voter<S: Storage>(storage: &S, address: &HumanAddr) -> StdResult<Voter> {
+    let mut voter = voters_read(storage)
+        .may_load(address.0.as_bytes())?
+        .unwrap_or_default();
+    for allocation in voter.allocations.iter_mut() {
+        while let Some(replacement) =
+            replaced_recipients_read(storage).may_load(allocation.recipient.0.as_bytes())?
+        {
+            allocation.recipient = replacement;
+        }
+    }
+
+    Ok(voter)
+}
+
+// Votes count for every epoch whose boundary they were in the tally at, so the Buttcoin behind them
+// has to stay locked until the end of the current epoch. This keeps voters from locking just before
+// a boundary and unlocking right after it.
+fn mark_voted_epoch(voter: &mut Voter, epoch: u64) {
+    let votes = !voter.locked.is_zero()
+        && voter
+            .allocations
+            .iter()
+            .any(|allocation| allocation.basis_points > 0);
+    if votes && voter.locked_in_epoch < epoch {
+        voter.locked_in_epoch = epoch;
+    }
+}
+
+fn vote_weight(voter: &Voter, allocation: &VoteAllocation) -> StdResult<u128> {
+    Ok(checked_mul(
+        voter.locked.u128(),
+        allocation.basis_points as u128,
+        "vote weight",
+    )? / MAX_BASIS_POINTS as u128)
+}
+
+fn add_votes(tally: &mut Vec<GaugeWeight>, voter: &Voter) -> StdResult<()> {
+    for allocation in &voter.allocations {
+        let weight = vote_weight(voter, allocation)?;
+        match tally
+            .iter_mut()
+            .find(|gauge_weight| gauge_weight.recipient == allocation.recipient)
+        {
+            Some(gauge_weight) => {
+                gauge_weight.weight = Uint128(checked_add(
+                    gauge_weight.weight.u128(),
+                    weight,
+                    "gauge weight",
+                )?)
+            }
+            None => tally.push(GaugeWeight {
+                recipient: allocation.recipient.clone(),
+                weight: Uint128(weight),
+            }),
+        }
+    }
+
+    Ok(())
+}
+
+fn remove_votes(tally: &mut Vec<GaugeWeight>, voter: &Voter) -> StdResult<()> {
+    for allocation in &voter.allocations {
+        let weight = Uint128(vote_weight(voter, allocation)?);
+        if let Some(gauge_weight) = tally
+            .iter_mut()
+            .find(|gauge_weight| gauge_weight.recipient == allocation.recipient)
+        {
+            gauge_weight.weight = (gauge_weight.weight - weight)?;
+        }
+    }
+    tally.retain(|gauge_weight| !gauge_weight.weight.is_zero());
+
+    Ok(())
+}
+
+// Sets the weight of every recipient to its share of the tally.
+fn apply_gauge_weights<S: Storage>(
+    storage: &mut S,
+    state: &mut State,
+    tally: &[GaugeWeight],
+) -> StdResult<Vec<GaugeWeight>> {
+    let gauge_weights = gauge_weights(tally);
+    let mut weights = vec![];
+    for address in recipient_addresses_read(storage)
+        .may_load()?
+        .unwrap_or_default()
+    {
+        let mut recipient = recipients_read(storage).load(address.0.as_bytes())?;
+        let weight = find_weight(&gauge_weights, &address);
+        change_recipient_weight(state, &mut recipient, weight)?;
+        recipients(storage).save(address.0.as_bytes(), &recipient)?;
+        weights.push(GaugeWeight {
+            recipient: address,
+            weight,
+        });
+    }
+
+    Ok(weights)
+}
+
+// Called by the registered contract itself, which proves that the address and code hash are right.
+// Registrations that are still pending when the configuration is frozen can no longer be accepted,
+// as that would change the weights.
+// While gauge voting is enabled new recipients start without weight until they are voted for.
+fn accept_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    contract_hash: String,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    assert_not_frozen(&state)?;
+    let sender = deps.api.canonical_address(&env.message.sender)?;
+    let mut pending = pending_recipients_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let index = match pending
+        .iter()
+        .position(|pending| pending.contract.address == sender)
+    {
+        Some(index) => index,
+        None => return Err(ContractError::NoPendingRegistration.into()),
+    };
+    if pending[index].contract.contract_hash != contract_hash {
+        return Err(ContractError::ContractHashMismatch.into());
+    }
+
+    let accepted = pending.remove(index);
+    pending_recipients(&mut deps.storage).save(&pending)?;
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    let mut messages = vec![];
+    let mut logs = vec![log("accepted_recipient", env.message.sender)];
+    match accepted.replaces {
+        Some(old) => {
+            let (replacement_messages, paid_amount) =
+                take_over_recipient(deps, &mut state, old.clone(), accepted.contract)?;
+            messages = replacement_messages;
+            logs.push(log("old_recipient", old));
+            logs.push(log("paid_amount", paid_amount));
+        }
+        None => {
+            let weight = if gauge_config_read(&deps.storage).may_load()?.is_some() {
+                Uint128(0)
+            } else {
+                accepted.weight
+            };
+            register_recipient(
+                deps,
+                &mut state,
+                accepted.contract,
+                weight,
+                accepted.delivery_mode,
+            )?;
+        }
+    }
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::AcceptRecipient { status: Success },
+        )?),
+    })
+}
+
+fn add_claim_operator<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    operator: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let operator = validate_address(&deps.api, &operator)?;
+    let key = env.message.sender.0.as_bytes();
+    let mut recipient = load_claim_operator_recipient(&deps.storage, key)?;
+    if recipient.claim_operators.contains(&operator) {
+        return Err(ContractError::AlreadyClaimOperator { operator }.into());
+    }
+
+    recipient.claim_operators.push(operator);
+    recipients(&mut deps.storage).save(key, &recipient)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::AddClaimOperator { status: Success },
+        )?),
+    })
+}
+
+fn remove_claim_operator<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    operator: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let key = env.message.sender.0.as_bytes();
+    let mut recipient = load_claim_operator_recipient(&deps.storage, key)?;
+    if !recipient.claim_operators.contains(&operator) {
+        return Err(ContractError::NotClaimOperator { operator }.into());
+    }
+
+    recipient
+        .claim_operators
+        .retain(|claim_operator| *claim_operator != operator);
+    recipients(&mut deps.storage).save(key, &recipient)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::RemoveClaimOperator { status: Success },
+        )?),
+    })
+}
+
+fn load_claim_operator_recipient<S: Storage>(storage: &S, key: &[u8]) -> StdResult<Recipient> {
+    match recipients_read(storage).may_load(key)? {
+        Some(recipient) => Ok(recipient),
+        None => Err(ContractError::ClaimOperatorsNeedRecipient.into()),
+    }
+}
+
+// A claim operator can claim for a recipient by passing it as the recipient. The payout and hook
+// still go to the recipient, which trusts the hook, so operators can only pass hooks of the
+// variants registered for the recipient.
+// Passing an amount only claims that much of what has accrued.
+fn claim_buttcoin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Option<Uint128>,
+    hook: Option<Binary>,
+    recipient: Option<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    let buttcoin = humanize_contract(&deps.api, &state.buttcoin)?;
+    let mut fees = load_fees(&deps.storage)?;
+    let mut rewards: u128 = 0;
+    let mut expired_messages = vec![];
+
+    let claimant = match recipient {
+        Some(recipient) if recipient != env.message.sender => {
+            let is_operator = matches!(
+                recipients_read(&deps.storage).may_load(recipient.0.as_bytes())?,
+                Some(recipient) if recipient.claim_operators.contains(&env.message.sender)
+            );
+            if !is_operator {
+                return Err(ContractError::Unauthorized.into());
+            }
+            recipient
+        }
+        _ => env.message.sender.clone(),
+    };
+    let key = claimant.0.as_bytes();
+    let recipient = recipients_read(&deps.storage).may_load(key)?;
+    if claimant != env.message.sender {
+        if let (Some(recipient), Some(hook)) = (&recipient, &hook) {
+            match &recipient.hook_variants {
+                Some(hook_variants) => assert_hook_variant(hook, hook_variants)?,
+                None => return Err(ContractError::HookNotAllowed.into()),
+            }
+        }
+    }
+    validate_hook(&deps.storage, recipient.as_ref(), &hook)?;
+    let mut contract_hash = String::new();
+    let mut delivery_mode = DeliveryMode::default();
+    if recipient.is_some() {
+        update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+        let mut recipient = recipients_read(&deps.storage).load(key)?;
+        settle_recipient(&mut state, &mut recipient)?;
+        let mut reward_expiry = reward_expiry_config_read(&deps.storage).may_load()?;
+        expired_messages = expire_recipient_rewards(
+            &mut state,
+            &buttcoin,
+            &mut reward_expiry,
+            &mut recipient,
+            env.block.height,
+        )?;
+        save_reward_expiry(&mut deps.storage, &reward_expiry)?;
+        if let Some(amount) = amount {
+            if amount > recipient.accrued {
+                return Err(ContractError::InsufficientFunds { balance: "accrued" }.into());
+            }
+        }
+        rewards = take_claimable(
+            &load_claim_limits(&deps.storage)?,
+            &state,
+            &mut recipient,
+            amount,
+            env.block.height,
+        )?;
+        recipients(&mut deps.storage).save(key, &recipient)?;
+        config(&mut deps.storage).save(&state)?;
+        contract_hash = recipient.contract.contract_hash;
+        delivery_mode = recipient.delivery_mode;
+    }
+
+    let (mut messages, treasury_amount, burned_amount) = take_fees(&buttcoin, &mut fees, rewards)?;
+    if treasury_amount > 0 || burned_amount > 0 {
+        fees_config(&mut deps.storage).save(&fees)?;
+    }
+    let sent_amount = amount_after_fees(rewards, treasury_amount, burned_amount)?;
+    messages.extend(delivery_messages(
+        &buttcoin,
+        &delivery_mode,
+        claimant.clone(),
+        contract_hash,
+        sent_amount,
+        hook,
+    )?);
+    messages.extend(expired_messages);
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::ClaimButtcoin {
+                burned_amount: Uint128(burned_amount),
+                sent_amount: Uint128(sent_amount),
+                status: Success,
+                treasury_amount: Uint128(treasury_amount),
+            },
+        )?),
+    })
+}
+
+// Hooks are forwarded as they are unless strict mode is on.
+fn validate_hook<S: Storage>(
+    storage: &S,
+    recipient: Option<&Recipient>,
+    hook: &Option<Binary>,
+) -> StdResult<()> {
+    let hook_validation = hook_validation_config_read(storage)
+        .may_load()?
+        .unwrap_or_default();
+    let hook = match hook {
+        Some(hook) if hook_validation.strict => hook,
+        _ => return Ok(()),
+    };
+
+    if hook.len() as u64 > hook_validation.max_size {
+        return Err(ContractError::HookTooLarge {
+            max_size: hook_validation.max_size,
+        }
+        .into());
+    }
+    match recipient.and_then(|recipient| recipient.hook_variants.as_ref()) {
+        Some(hook_variants) => assert_hook_variant(hook, hook_variants)?,
+        None => {
+            from_binary::<YieldOptimizerDepositButtcoinHookMsg>(hook)
+                .map_err(|_| StdError::from(ContractError::InvalidHook))?;
+        }
+    }
+
+    Ok(())
+}
+
+// The hook has to be a single variant out of hook_variants.
+fn assert_hook_variant(hook: &Binary, hook_variants: &[String]) -> StdResult<()> {
+    let hook: BTreeMap<String, IgnoredAny> =
+        from_binary(hook).map_err(|_| StdError::from(ContractError::HookNotAllowed))?;
+    if hook.len() != 1 || !hook.keys().all(|variant| hook_variants.contains(variant)) {
+        return Err(ContractError::HookNotAllowed.into());
+    }
+
+    Ok(())
+}
+
+// contract_hash is only needed to notify the recipient.
+fn delivery_messages(
+    buttcoin: &SecretContract,
+    delivery_mode: &DeliveryMode,
+    recipient: HumanAddr,
+    contract_hash: String,
+    amount: u128,
+    hook: Option<Binary>,
+) -> StdResult<Vec<CosmosMsg>> {
+    Ok(match delivery_mode {
+        DeliveryMode::DepositButtcoin {} => vec![snip20::send_msg(
+            recipient,
+            Uint128(amount),
+            Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin {
+                hook,
+            })?),
+            None,
+            1,
+            buttcoin.contract_hash.clone(),
+            buttcoin.address.clone(),
+        )?],
+        DeliveryMode::Send { msg } => vec![snip20::send_msg(
+            recipient,
+            Uint128(amount),
+            msg.as_ref().map(|msg| render_delivery_msg(msg, amount)),
+            None,
+            1,
+            buttcoin.contract_hash.clone(),
+            buttcoin.address.clone(),
+        )?],
+        DeliveryMode::SendAndNotify { msg } => vec![
+            snip20::send_msg(
+                recipient.clone(),
+                Uint128(amount),
+                None,
+                None,
+                1,
+                buttcoin.contract_hash.clone(),
+                buttcoin.address.clone(),
+            )?,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: recipient,
+                callback_code_hash: contract_hash,
+                msg: render_delivery_msg(msg, amount),
+                send: vec![],
+            }),
+        ],
+        DeliveryMode::Transfer {} => vec![snip20::transfer_msg(
+            recipient,
+            Uint128(amount),
+            None,
+            1,
+            buttcoin.contract_hash.clone(),
+            buttcoin.address.clone(),
+        )?],
+    })
+}
+
+fn render_delivery_msg(msg: &Binary, amount: u128) -> Binary {
+    match std::str::from_utf8(msg.as_slice()) {
+        Ok(msg) => Binary::from(
+            msg.replace("{{amount}}", &amount.to_string())
+                .as_bytes()
+                .to_vec(),
+        ),
+        Err(_) => msg.clone(),
+    }
+}
+
+// Takes the treasury fee and the burn out of a payout before the rest goes to the recipient.
+// Returns the messages for them along with the treasury and burned amounts.
+fn take_fees(
+    buttcoin: &SecretContract,
+    fees: &mut Fees,
+    amount: u128,
+) -> StdResult<(Vec<CosmosMsg>, u128, u128)> {
+    let mut messages = vec![];
+    let treasury_amount = checked_mul(amount, fees.treasury_basis_points as u128, "treasury fee")?
+        / MAX_BASIS_POINTS as u128;
+    let burned_amount =
+        checked_mul(amount, fees.burn_basis_points as u128, "burn")? / MAX_BASIS_POINTS as u128;
+
+    if treasury_amount > 0 {
+        if let Some(treasury) = fees.treasury.clone() {
+            messages.push(snip20::transfer_msg(
+                treasury,
+                Uint128(treasury_amount),
+                None,
+                1,
+                buttcoin.contract_hash.clone(),
+                buttcoin.address.clone(),
+            )?);
+        }
+        fees.total_treasury = Uint128(checked_add(
+            fees.total_treasury.u128(),
+            treasury_amount,
+            "total treasury fees",
+        )?);
+    }
+    if burned_amount > 0 {
+        messages.push(snip20::burn_msg(
+            Uint128(burned_amount),
+            None,
+            1,
+            buttcoin.contract_hash.clone(),
+            buttcoin.address.clone(),
+        )?);
+        fees.total_burned = Uint128(checked_add(
+            fees.total_burned.u128(),
+            burned_amount,
+            "total burned",
+        )?);
+    }
+
+    Ok((messages, treasury_amount, burned_amount))
+}
+
+fn amount_after_fees(amount: u128, treasury_amount: u128, burned_amount: u128) -> StdResult<u128> {
+    checked_sub(
+        checked_sub(amount, treasury_amount, "amount after fees")?,
+        burned_amount,
+        "amount after fees",
+    )
+}
+
+// Takes what can be paid out of the recipient's accrued Buttcoin, up to the requested amount and
+// the claim limits.
+fn take_claimable(
+    claim_limits: &ClaimLimits,
+    state: &State,
+    recipient: &mut Recipient,
+    requested: Option<Uint128>,
+    block: u64,
+) -> StdResult<u128> {
+    let mut amount = requested.unwrap_or(recipient.accrued).u128();
+    amount = amount.min(recipient.accrued.u128());
+    if let Some(max_per_claim) = claim_limits.max_per_claim {
+        amount = amount.min(max_per_claim.u128());
+    }
+    if let Some(max_per_epoch) = claim_limits.max_per_epoch {
+        let epoch = block.saturating_sub(state.starting_block) / claim_limits.epoch_length;
+        if recipient.claim_epoch != epoch {
+            recipient.claim_epoch = epoch;
+            recipient.claimed_in_epoch = Uint128(0);
+        }
+        amount = amount.min(
+            max_per_epoch
+                .u128()
+                .saturating_sub(recipient.claimed_in_epoch.u128()),
+        );
+        recipient.claimed_in_epoch = Uint128(checked_add(
+            recipient.claimed_in_epoch.u128(),
+            amount,
+            "claimed in epoch",
+        )?);
+    }
+    recipient.accrued = Uint128(checked_sub(
+        recipient.accrued.u128(),
+        amount,
+        "accrued rewards",
+    )?);
+    // Payouts come out of the oldest buckets first, and then out of what never expires
+    let mut remaining = amount;
+    for bucket in recipient.accrual_buckets.iter_mut() {
+        let taken = remaining.min(bucket.amount.u128());
+        bucket.amount = Uint128(checked_sub(
+            bucket.amount.u128(),
+            taken,
+            "expiring rewards",
+        )?);
+        remaining = checked_sub(remaining, taken, "expiring rewards")?;
+    }
+    recipient
+        .accrual_buckets
+        .retain(|bucket| !bucket.amount.is_zero());
+    recipient.unexpiring = Uint128(recipient.unexpiring.u128().saturating_sub(remaining));
+
+    Ok(amount)
+}
+
+// Puts what has accrued since the last time into the buckets of the blocks it was earned in and
+// forfeits the buckets that have expired.
+// Accrual isn't recorded per block, so it is spread evenly over the blocks since the recipient was
+// last put in buckets, up to the end block. Blocks whose bucket has already expired share a single
+// bucket, and when more than MAX_NEW_ACCRUAL_BUCKETS buckets are still live, neighbouring ones are
+// merged and expire with the last of them, so that nothing is forfeited early.
+fn expire_recipient_rewards(
+    state: &mut State,
+    buttcoin: &SecretContract,
+    reward_expiry: &mut Option<RewardExpiry>,
+    recipient: &mut Recipient,
+    block: u64,
+) -> StdResult<Vec<CosmosMsg>> {
+    let reward_expiry = match reward_expiry {
+        Some(reward_expiry) => reward_expiry,
+        None => return Ok(vec![]),
+    };
+    let expiry_blocks = match reward_expiry.expiry_blocks {
+        Some(expiry_blocks) => expiry_blocks,
+        None => return Ok(vec![]),
+    };
+    let bucket_length = reward_expiry.bucket_length;
+    // Saturates, as a block that doesn't fit in a u64 is never reached
+    let expires_at_block = |earned_block: u64| {
+        (earned_block / bucket_length)
+            .saturating_add(1)
+            .saturating_mul(bucket_length)
+            .saturating_add(expiry_blocks)
+    };
+
+    let bucketed = recipient
+        .accrual_buckets
+        .iter()
+        .try_fold(0, |bucketed, bucket| {
+            checked_add(bucketed, bucket.amount.u128(), "expiring rewards")
+        })?;
+    let unbucketed = recipient
+        .accrued
+        .u128()
+        .saturating_sub(bucketed)
+        .saturating_sub(recipient.unexpiring.u128());
+    let from_block = recipient
+        .bucketed_until_block
+        .max(reward_expiry.counted_from_block);
+    let to_block = block.min(state.end_block);
+    if unbucketed > 0 && to_block > from_block {
+        let blocks = (to_block - from_block) as u128;
+        let live_from_block =
+            (block.saturating_sub(expiry_blocks) / bucket_length).saturating_mul(bucket_length);
+        let live_buckets = if to_block > live_from_block {
+            (to_block - 1) / bucket_length - live_from_block.max(from_block) / bucket_length + 1
+        } else {
+            0
+        };
+        let merged_buckets = (live_buckets / MAX_NEW_ACCRUAL_BUCKETS
+            + (live_buckets % MAX_NEW_ACCRUAL_BUCKETS != 0) as u64)
+            .max(1);
+        let merged_length = bucket_length.saturating_mul(merged_buckets);
+        let mut remaining = unbucketed;
+        let mut start = from_block;
+        while start < to_block {
+            let (end, expires_at) = if start < live_from_block {
+                (live_from_block.min(to_block), expires_at_block(start))
+            } else {
+                let end = (start / merged_length)
+                    .saturating_add(1)
+                    .saturating_mul(merged_length)
+                    .min(to_block);
+                (end, expires_at_block(end - 1))
+            };
+            let amount = if end == to_block {
+                remaining
+            } else {
+                checked_mul(unbucketed, (end - start) as u128, "expiring rewards")? / blocks
+            };
+            remaining = checked_sub(remaining, amount, "expiring rewards")?;
+            add_to_accrual_bucket(recipient, amount, expires_at)?;
+            start = end;
+        }
+    } else if unbucketed > 0 {
+        add_to_accrual_bucket(recipient, unbucketed, expires_at_block(block))?;
+    }
+    recipient.bucketed_until_block = recipient.bucketed_until_block.max(block);
+
+    let (expired_buckets, live_buckets): (Vec<AccrualBucket>, Vec<AccrualBucket>) = recipient
+        .accrual_buckets
+        .drain(..)
+        .partition(|bucket| bucket.expires_at_block <= block);
+    recipient.accrual_buckets = live_buckets;
+    let expired = expired_buckets.iter().try_fold(0, |expired, bucket| {
+        checked_add(expired, bucket.amount.u128(), "expired rewards")
+    })?;
+    if expired == 0 {
+        return Ok(vec![]);
+    }
+
+    recipient.accrued = Uint128(checked_sub(
+        recipient.accrued.u128(),
+        expired,
+        "accrued rewards",
+    )?);
+    reward_expiry.total_expired = Uint128(checked_add(
+        reward_expiry.total_expired.u128(),
+        expired,
+        "total expired",
+    )?);
+    match &reward_expiry.destination {
+        // Spread over the weights at the next update, the same as Buttcoin released with no weight
+        ExpiryDestination::Redistribute {} => {
+            state.unallocated_rewards = Uint128(checked_add(
+                state.unallocated_rewards.u128(),
+                expired,
+                "unallocated rewards",
+            )?);
+            Ok(vec![])
+        }
+        ExpiryDestination::Treasury { address } => Ok(vec![snip20::transfer_msg(
+            address.clone(),
+            Uint128(expired),
+            None,
+            1,
+            buttcoin.contract_hash.clone(),
+            buttcoin.address.clone(),
+        )?]),
+    }
+}
+
+// Buckets are kept in the order they expire in.
+fn add_to_accrual_bucket(
+    recipient: &mut Recipient,
+    amount: u128,
+    expires_at_block: u64,
+) -> StdResult<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    match recipient.accrual_buckets.last_mut() {
+        Some(bucket) if bucket.expires_at_block == expires_at_block => {
+            bucket.amount = Uint128(checked_add(
+                bucket.amount.u128(),
+                amount,
+                "expiring rewards",
+            )?);
+        }
+        _ => recipient.accrual_buckets.push(AccrualBucket {
+            amount: Uint128(amount),
+            expires_at_block,
+        }),
+    }
+
+    Ok(())
+}
+
+fn save_reward_expiry<S: Storage>(
+    storage: &mut S,
+    reward_expiry: &Option<RewardExpiry>,
+) -> StdResult<()> {
+    if let Some(reward_expiry) = reward_expiry {
+        reward_expiry_config(storage).save(reward_expiry)?;
+    }
+
+    Ok(())
+}
+
+fn load_claim_limits<S: Storage>(storage: &S) -> StdResult<ClaimLimits> {
+    Ok(claim_limits_config_read(storage)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+fn load_fees<S: Storage>(storage: &S) -> StdResult<Fees> {
+    Ok(fees_config_read(storage).may_load()?.unwrap_or_default())
+}
+
+// Pays out up to max_recipients_per_call recipients, starting where the previous call stopped, so
+// that everyone gets paid across calls no matter how many recipients there are.
+fn distribute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    let buttcoin = humanize_contract(&deps.api, &state.buttcoin)?;
+    let mut distribution = load_distribution(&deps.storage)?;
+    let mut fees = load_fees(&deps.storage)?;
+    let claim_limits = load_claim_limits(&deps.storage)?;
+    let mut reward_expiry = reward_expiry_config_read(&deps.storage).may_load()?;
+    let addresses = recipient_addresses_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let mut messages = vec![];
+    let mut distributed_amount: u128 = 0;
+
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    let recipients_count = addresses.len() as u64;
+    let distributed_count = distribution.max_recipients_per_call.min(recipients_count);
+    for i in 0..distributed_count {
+        let index = (distribution.cursor + i) % recipients_count;
+        let key = addresses[index as usize].0.as_bytes();
+        let mut recipient = recipients_read(&deps.storage).load(key)?;
+        settle_recipient(&mut state, &mut recipient)?;
+        messages.extend(expire_recipient_rewards(
+            &mut state,
+            &buttcoin,
+            &mut reward_expiry,
+            &mut recipient,
+            env.block.height,
+        )?);
+        let amount = take_claimable(
+            &claim_limits,
+            &state,
+            &mut recipient,
+            None,
+            env.block.height,
+        )?;
+        if amount > 0 {
+            distributed_amount = checked_add(distributed_amount, amount, "distributed amount")?;
+            let (fee_messages, treasury_amount, burned_amount) =
+                take_fees(&buttcoin, &mut fees, amount)?;
+            messages.extend(fee_messages);
+            messages.extend(delivery_messages(
+                &buttcoin,
+                &recipient.delivery_mode,
+                addresses[index as usize].clone(),
+                recipient.contract.contract_hash.clone(),
+                amount_after_fees(amount, treasury_amount, burned_amount)?,
+                distribution.hook.clone(),
+            )?);
+        }
+        recipients(&mut deps.storage).save(key, &recipient)?;
+    }
+    if recipients_count > 0 {
+        distribution.cursor = (distribution.cursor + distributed_count) % recipients_count;
+    }
+    distribution_config(&mut deps.storage).save(&distribution)?;
+    fees_config(&mut deps.storage).save(&fees)?;
+    save_reward_expiry(&mut deps.storage, &reward_expiry)?;
+
+    let mut bounty: u128 = 0;
+    if let Some(mut keeper) = keeper_config_read(&deps.storage).may_load()? {
+        bounty = pay_keeper_bounty(
+            &mut state,
+            &mut keeper,
+            distributed_amount,
+            env.block.height,
+        )?;
+        if bounty > 0 {
+            messages.push(snip20::transfer_msg(
+                env.message.sender,
+                Uint128(bounty),
+                None,
+                1,
+                buttcoin.contract_hash.clone(),
+                buttcoin.address.clone(),
+            )?);
+            keeper_config(&mut deps.storage).save(&keeper)?;
+        }
+    }
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("distributed_recipients", distributed_count),
+            log("next_cursor", distribution.cursor),
+            log("keeper_bounty", bounty),
+        ],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::Distribute {
+            status: Success,
+        })?),
+    })
+}
+
+// Lets anyone put the accrued Buttcoin of recipients that have stopped claiming up for expiry, and
+// forfeit what has expired.
+fn expire_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    addresses: Vec<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    let buttcoin = humanize_contract(&deps.api, &state.buttcoin)?;
+    let mut reward_expiry = reward_expiry_config_read(&deps.storage).may_load()?;
+    let total_expired_before = reward_expiry
+        .as_ref()
+        .map_or(0, |reward_expiry| reward_expiry.total_expired.u128());
+    let mut messages = vec![];
+
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    for address in addresses {
+        let key = address.0.as_bytes();
+        let mut recipient = match recipients_read(&deps.storage).may_load(key)? {
+            Some(recipient) => recipient,
+            None => return Err(ContractError::NotRecipient { address }.into()),
+        };
+        settle_recipient(&mut state, &mut recipient)?;
+        messages.extend(expire_recipient_rewards(
+            &mut state,
+            &buttcoin,
+            &mut reward_expiry,
+            &mut recipient,
+            env.block.height,
+        )?);
+        recipients(&mut deps.storage).save(key, &recipient)?;
+    }
+    save_reward_expiry(&mut deps.storage, &reward_expiry)?;
+    config(&mut deps.storage).save(&state)?;
+    let expired_amount = checked_sub(
+        reward_expiry
+            .as_ref()
+            .map_or(0, |reward_expiry| reward_expiry.total_expired.u128()),
+        total_expired_before,
+        "expired amount",
+    )?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![log("expired_amount", expired_amount)],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::ExpireRewards { status: Success },
+        )?),
+    })
+}
+
+// Only cranks that actually distribute something are paid, so that calling Distribute over and
+// over can't farm the bounty.
+// Bounties count towards max_total_emission like everything else.
+fn pay_keeper_bounty(
+    state: &mut State,
+    keeper: &mut Keeper,
+    distributed_amount: u128,
+    block: u64,
+) -> StdResult<u128> {
+    if distributed_amount == 0 {
+        return Ok(0);
+    }
+    if let Some(last_paid_block) = keeper.last_paid_block {
+        if block < last_paid_block.saturating_add(keeper.min_interval) {
+            return Ok(0);
+        }
+    }
+
+    let bounty = match keeper.bounty {
+        KeeperBounty::BasisPoints { basis_points, cap } => {
+            (checked_mul(distributed_amount, basis_points as u128, "keeper bounty")?
+                / MAX_BASIS_POINTS as u128)
+                .min(cap.u128())
+        }
+        KeeperBounty::Fixed { amount } => amount.u128(),
+    }
+    .min(emission_headroom(state));
+    if bounty > 0 {
+        state.total_emitted = Uint128(checked_add(
+            state.total_emitted.u128(),
+            bounty,
+            "total emitted",
+        )?);
+        keeper.last_paid_block = Some(block);
+        keeper.paid_cranks = keeper
+            .paid_cranks
+            .checked_add(1)
+            .ok_or(ContractError::Overflow {
+                value: "paid cranks",
+            })?;
+        keeper.total_paid = Uint128(checked_add(
+            keeper.total_paid.u128(),
+            bounty,
+            "total keeper bounties",
+        )?);
+    }
+
+    Ok(bounty)
+}
+
+fn load_distribution<S: Storage>(storage: &S) -> StdResult<Distribution> {
+    Ok(distribution_config_read(storage)
+        .may_load()?
+        .unwrap_or(Distribution {
+            cursor: 0,
+            hook: None,
+            max_recipients_per_call: DEFAULT_MAX_RECIPIENTS_PER_DISTRIBUTE,
+        }))
+}
+
+fn receive<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    amount: Uint128,
+    msg: Option<Binary>,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    if env.message.sender != deps.api.human_address(&state.buttcoin.address)? {
+        return Err(ContractError::Unauthorized.into());
+    }
+
+    // Buttcoin sent without a message funds the distribution
+    match msg {
+        Some(msg) => match from_binary(&msg)? {
+            ButtcoinDistributorReceiveMsg::LockButtcoin {} => {
+                lock_buttcoin(deps, env, from, amount)?
+            }
+        },
+        // Brought up to date first, so that the funding isn't released for the blocks before it
+        None => {
+            update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+            state.total_funded = Uint128(checked_add(
+                state.total_funded.u128(),
+                amount.u128(),
+                "total funded",
+            )?);
+            config(&mut deps.storage).save(&state)?;
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::Receive {
+            status: Success,
+        })?),
+    })
+}
+
+// Votes are counted from the next epoch boundary, so Buttcoin locked in an epoch stays locked
+// until that boundary has passed.
+// Buttcoin that votes stays locked as well, see mark_voted_epoch.
+fn lock_buttcoin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut state = config_read(&deps.storage).load()?;
+    load_gauge(&deps.storage)?;
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    let mut gauge = load_gauge(&deps.storage)?;
+    let mut voter = load_voter(&deps.storage, &from)?;
+
+    remove_votes(&mut gauge.tally, &voter)?;
+    voter.locked = Uint128(checked_add(
+        voter.locked.u128(),
+        amount.u128(),
+        "locked Buttcoin",
+    )?);
+    voter.locked_in_epoch = gauge.epoch;
+    add_votes(&mut gauge.tally, &voter)?;
+    gauge.total_locked = Uint128(checked_add(
+        gauge.total_locked.u128(),
+        amount.u128(),
+        "total locked Buttcoin",
+    )?);
+
+    voters(&mut deps.storage).save(from.0.as_bytes(), &voter)?;
+    gauge_config(&mut deps.storage).save(&gauge)?;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(())
+}
+
+fn unlock_buttcoin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Uint128,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    let buttcoin = humanize_contract(&deps.api, &state.buttcoin)?;
+    load_gauge(&deps.storage)?;
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    let mut gauge = load_gauge(&deps.storage)?;
+    let key = env.message.sender.0.as_bytes();
+    let mut voter = load_voter(&deps.storage, &env.message.sender)?;
+    if amount > voter.locked {
+        return Err(ContractError::InsufficientFunds { balance: "locked" }.into());
+    }
+    mark_voted_epoch(&mut voter, gauge.epoch);
+    if voter.locked_in_epoch >= gauge.epoch {
+        return Err(ContractError::UnlockTooEarly.into());
+    }
+
+    remove_votes(&mut gauge.tally, &voter)?;
+    voter.locked = (voter.locked - amount)?;
+    add_votes(&mut gauge.tally, &voter)?;
+    gauge.total_locked = (gauge.total_locked - amount)?;
+
+    voters(&mut deps.storage).save(key, &voter)?;
+    gauge_config(&mut deps.storage).save(&gauge)?;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![snip20::transfer_msg(
+            env.message.sender.clone(),
+            amount,
+            None,
+            1,
+            buttcoin.contract_hash,
+            buttcoin.address,
+        )?],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::UnlockButtcoin { status: Success },
+        )?),
+    })
+}
+
+// Replaces the previous allocations of the voter.
+fn vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    allocations: Vec<VoteAllocation>,
+) -> StdResult<HandleResponse> {
+    load_gauge(&deps.storage)?;
+    let mut total_basis_points: u32 = 0;
+    for (i, allocation) in allocations.iter().enumerate() {
+        if allocations[..i]
+            .iter()
+            .any(|other| other.recipient == allocation.recipient)
+        {
+            return Err(ContractError::DuplicateVote.into());
+        }
+        if recipients_read(&deps.storage)
+            .may_load(allocation.recipient.0.as_bytes())?
+            .is_none()
+        {
+            return Err(ContractError::NotRecipient {
+                address: allocation.recipient.clone(),
+            }
+            .into());
+        }
+        total_basis_points += allocation.basis_points as u32;
+    }
+    if total_basis_points > MAX_BASIS_POINTS as u32 {
+        return Err(ContractError::TooManyBasisPoints {
+            subject: "Allocations",
+        }
+        .into());
+    }
+
+    let mut state = config_read(&deps.storage).load()?;
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    let mut gauge = load_gauge(&deps.storage)?;
+    let key = env.message.sender.0.as_bytes();
+    let mut voter = load_voter(&deps.storage, &env.message.sender)?;
+
+    mark_voted_epoch(&mut voter, gauge.epoch);
+    remove_votes(&mut gauge.tally, &voter)?;
+    voter.allocations = allocations;
+    add_votes(&mut gauge.tally, &voter)?;
+
+    voters(&mut deps.storage).save(key, &voter)?;
+    gauge_config(&mut deps.storage).save(&gauge)?;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::Vote {
+            status: Success,
+        })?),
+    })
+}
+
+// === PRIVILEGED ===
+
+// Privileged handles are executed straight away when called by the admin.
+// When a multisig is enabled they become proposals instead and are executed once enough signers
+// have approved them.
+fn submit_privileged<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: ButtcoinDistributorHandleMsg,
+) -> StdResult<HandleResponse> {
+    let state = config_read(&deps.storage).load()?;
+    assert_not_frozen(&state)?;
+
+    match multisig_config_read(&deps.storage).may_load()? {
+        Some(multisig) if multisig.is_enabled() => create_proposal(deps, env, multisig, msg),
+        _ => {
+            if env.message.sender != state.admin {
+                return Err(ContractError::Unauthorized.into());
+            }
+
+            execute_privileged(deps, env, msg)
+        }
+    }
+}
+
+// Checked again at execution so that proposals created before the configuration was frozen can
+// not be executed afterwards.
+fn execute_privileged<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: ButtcoinDistributorHandleMsg,
+) -> StdResult<HandleResponse> {
+    assert_not_frozen(&config_read(&deps.storage).load()?)?;
+
+    match msg {
+        ButtcoinDistributorHandleMsg::AddRecipient {
+            delivery_mode,
+            recipient,
+            weight,
+        } => add_recipient(deps, recipient, weight, delivery_mode.unwrap_or_default()),
+        ButtcoinDistributorHandleMsg::ChangeAdmin { admin } => change_admin(deps, admin),
+        ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length } => {
+            enable_gauge_voting(deps, env, epoch_length)
+        }
+        ButtcoinDistributorHandleMsg::RenounceAdmin {} => renounce_admin(deps),
+        ButtcoinDistributorHandleMsg::ReplaceRecipient { old, new } => {
+            replace_recipient(deps, old, new)
+        }
+        ButtcoinDistributorHandleMsg::SetClaimLimits {
+            epoch_length,
+            max_per_claim,
+            max_per_epoch,
+        } => set_claim_limits(deps, epoch_length, max_per_claim, max_per_epoch),
+        ButtcoinDistributorHandleMsg::SetDistribution {
+            hook,
+            max_recipients_per_call,
+        } => set_distribution(deps, hook, max_recipients_per_call),
+        ButtcoinDistributorHandleMsg::SetFees {
+            burn_basis_points,
+            treasury,
+            treasury_basis_points,
+        } => set_fees(deps, burn_basis_points, treasury, treasury_basis_points),
+        ButtcoinDistributorHandleMsg::SetHookValidation { max_size, strict } => {
+            set_hook_validation(deps, max_size, strict)
+        }
+        ButtcoinDistributorHandleMsg::SetRecipientHookVariants {
+            address,
+            hook_variants,
+        } => set_recipient_hook_variants(deps, address, hook_variants),
+        ButtcoinDistributorHandleMsg::SetKeeper {
+            bounty,
+            min_interval,
+        } => set_keeper(deps, bounty, min_interval),
+        ButtcoinDistributorHandleMsg::SetMultisig {
+            signers,
+            threshold,
+            proposal_expiry,
+        } => set_multisig(deps, signers, threshold, proposal_expiry),
+        ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
+            receivable_smart_contract,
+        } => set_receivable_smart_contract(deps, receivable_smart_contract),
+        ButtcoinDistributorHandleMsg::SetRecipientWeight { address, weight } => {
+            set_recipient_weight(deps, env, address, weight)
+        }
+        ButtcoinDistributorHandleMsg::SetRewardExpiry {
+            bucket_length,
+            destination,
+            expiry_blocks,
+        } => set_reward_expiry(deps, env, bucket_length, destination, expiry_blocks),
+        ButtcoinDistributorHandleMsg::SetSchedule {
+            end_block,
+            max_total_emission,
+            release_per_block,
+            release_per_block_denominator,
+        } => set_schedule(
+            deps,
+            env,
+            end_block,
+            max_total_emission,
+            release_per_block,
+            release_per_block_denominator,
+        ),
+        _ => Err(ContractError::NotPrivileged.into()),
+    }
+}
+
+fn create_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    mut multisig: Multisig,
+    msg: ButtcoinDistributorHandleMsg,
+) -> StdResult<HandleResponse> {
+    if !multisig.signers.contains(&env.message.sender) {
+        return Err(ContractError::Unauthorized.into());
+    }
+
+    prune_pending_proposals(&deps.storage, &mut multisig, env.block.height)?;
+    multisig.proposal_count += 1;
+    let proposal = Proposal {
+        approvals: vec![env.message.sender.clone()],
+        executed: false,
+        expires_at_block: env.block.height.saturating_add(multisig.proposal_expiry),
+        id: multisig.proposal_count,
+        msg,
+    };
+    multisig.pending_proposal_ids.push(proposal.id);
+    proposals(&mut deps.storage).save(&proposal.id.to_be_bytes(), &proposal)?;
+    multisig_config(&mut deps.storage).save(&multisig)?;
+
+    if proposal.approvals.len() as u64 >= multisig.threshold {
+        return execute_proposal(deps, env, multisig, proposal);
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("proposal_id", proposal.id)],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::ProposalCreated {
+                id: proposal.id,
+                status: Success,
+            },
+        )?),
+    })
+}
+
+fn approve_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u64,
+) -> StdResult<HandleResponse> {
+    let multisig = match multisig_config_read(&deps.storage).may_load()? {
+        Some(multisig) if multisig.is_enabled() => multisig,
+        _ => return Err(ContractError::MultisigNotEnabled.into()),
+    };
+    if !multisig.signers.contains(&env.message.sender) {
+        return Err(ContractError::Unauthorized.into());
+    }
+
+    let mut proposal = match proposals_read(&deps.storage).may_load(&id.to_be_bytes())? {
+        Some(proposal) => proposal,
+        None => return Err(ContractError::ProposalNotFound { id }.into()),
+    };
+    if proposal.executed {
+        return Err(ContractError::ProposalExecuted.into());
+    }
+    if !multisig.pending_proposal_ids.contains(&id) {
+        return Err(ContractError::ProposalCancelled.into());
+    }
+    if env.block.height > proposal.expires_at_block {
+        return Err(ContractError::ProposalExpired.into());
+    }
+    if proposal.approvals.contains(&env.message.sender) {
+        return Err(ContractError::ProposalAlreadyApproved.into());
+    }
+
+    proposal.approvals.push(env.message.sender.clone());
+    proposals(&mut deps.storage).save(&id.to_be_bytes(), &proposal)?;
+    if proposal.approvals.len() as u64 >= multisig.threshold {
+        return execute_proposal(deps, env, multisig, proposal);
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("proposal_id", id)],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::ApproveProposal { status: Success },
+        )?),
+    })
+}
+
+// The proposal is marked as executed before running it, so that a proposal that changes the
+// multisig itself starts from an up to date signer set.
+fn execute_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    mut multisig: Multisig,
+    mut proposal: Proposal,
+) -> StdResult<HandleResponse> {
+    proposal.executed = true;
+    proposals(&mut deps.storage).save(&proposal.id.to_be_bytes(), &proposal)?;
+    multisig
+        .pending_proposal_ids
+        .retain(|id| *id != proposal.id);
+    multisig_config(&mut deps.storage).save(&multisig)?;
+
+    let mut response = execute_privileged(deps, env, proposal.msg)?;
+    response.log.push(log("executed_proposal_id", proposal.id));
+    Ok(response)
+}
+
+fn assert_not_frozen(state: &State) -> StdResult<()> {
+    if state.frozen {
+        return Err(ContractError::Frozen.into());
+    }
+
+    Ok(())
+}
+
+fn prune_pending_proposals<S: Storage>(
+    storage: &S,
+    multisig: &mut Multisig,
+    block: u64,
+) -> StdResult<()> {
+    let mut pending_proposal_ids = vec![];
+    for id in &multisig.pending_proposal_ids {
+        let proposal = proposals_read(storage).load(&id.to_be_bytes())?;
+        if !proposal.executed && block <= proposal.expires_at_block {
+            pending_proposal_ids.push(*id);
+        }
+    }
+    multisig.pending_proposal_ids = pending_proposal_ids;
+
+    Ok(())
+}
+
+fn add_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    recipient: SecretContract,
+    weight: Uint128,
+    delivery_mode: DeliveryMode,
+) -> StdResult<HandleResponse> {
+    // Gauge voting sets the weight once the recipient is accepted
+    if gauge_config_read(&deps.storage).may_load()?.is_none() {
+        validate_total_weight(&config_read(&deps.storage).load()?, Uint128(0), weight)?;
+    }
+    register_pending_recipient(deps, recipient, weight, delivery_mode, None)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::AddRecipient {
+            status: Success,
+        })?),
+    })
+}
+
+fn change_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    admin: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    state.admin = validate_address(&deps.api, &admin)?;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::ChangeAdmin {
+            status: Success,
+        })?),
+    })
+}
+
+// From the next epoch boundary on, recipient weights are taken from the votes of Buttcoin lockers.
+// The current weights are kept until then, and whenever nobody has voted.
+fn enable_gauge_voting<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    epoch_length: u64,
+) -> StdResult<HandleResponse> {
+    if gauge_config_read(&deps.storage).may_load()?.is_some() {
+        return Err(ContractError::GaugeVotingAlreadyEnabled.into());
+    }
+    if epoch_length == 0 {
+        return Err(ContractError::MustBePositive {
+            field: "Epoch length",
+        }
+        .into());
+    }
+
+    let mut state = config_read(&deps.storage).load()?;
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    state.gauge_voting = true;
+    let mut gauge = Gauge {
+        epoch: 0,
+        epoch_length,
+        latest_snapshot_epoch: 0,
+        tally: vec![],
+        total_locked: Uint128(0),
+    };
+    gauge.epoch = gauge_epoch(&gauge, &state, env.block.height);
+    gauge.latest_snapshot_epoch = gauge.epoch;
+    let mut weights = vec![];
+    for address in recipient_addresses_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+    {
+        let recipient = recipients_read(&deps.storage).load(address.0.as_bytes())?;
+        weights.push(GaugeWeight {
+            recipient: address,
+            weight: recipient.weight,
+        });
+    }
+    gauge_epochs(&mut deps.storage).save(
+        &gauge.epoch.to_be_bytes(),
+        &GaugeEpoch {
+            epoch: gauge.epoch,
+            previous_snapshot_epoch: None,
+            weights,
+        },
+    )?;
+    gauge_config(&mut deps.storage).save(&gauge)?;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::EnableGaugeVoting { status: Success },
+        )?),
+    })
+}
+
+// Irreversible. Every privileged handle is disabled from here on, while claiming and funding keep
+// working with the schedule as it is.
+fn renounce_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    state.frozen = true;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::RenounceAdmin { status: Success },
+        )?),
+    })
+}
+
+// The new recipient only takes over from the old one once it accepts, so that a mistyped address
+// never earns anything.
+fn replace_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    old: HumanAddr,
+    new: SecretContract,
+) -> StdResult<HandleResponse> {
+    let old_recipient = match recipients_read(&deps.storage).may_load(old.0.as_bytes())? {
+        Some(old_recipient) => old_recipient,
+        None => return Err(ContractError::NotRecipient { address: old }.into()),
+    };
+    if pending_recipients_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .iter()
+        .any(|pending| pending.replaces.as_ref() == Some(&old))
+    {
+        return Err(ContractError::AlreadyPending.into());
+    }
+
+    let new_address = new.address.clone();
+    register_pending_recipient(
+        deps,
+        new,
+        old_recipient.weight,
+        old_recipient.delivery_mode,
+        Some(old.clone()),
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("old_recipient", old), log("new_recipient", new_address)],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::ReplaceRecipient { status: Success },
+        )?),
+    })
+}
+
+// The old recipient is paid everything it has earned up to now before the new recipient takes
+// over its weight, votes, delivery mode and place in the distribution order.
+// The pool has to be up to date. Returns the payout messages and the amount paid to the old
+// recipient.
+fn take_over_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    state: &mut State,
+    old: HumanAddr,
+    new: CanonicalSecretContract,
+) -> StdResult<(Vec<CosmosMsg>, u128)> {
+    let buttcoin = humanize_contract(&deps.api, &state.buttcoin)?;
+    let new_address = deps.api.human_address(&new.address)?;
+    let old_key = old.0.as_bytes();
+    let mut old_recipient = match recipients_read(&deps.storage).may_load(old_key)? {
+        Some(old_recipient) => old_recipient,
+        None => return Err(ContractError::NotRecipient { address: old }.into()),
+    };
+    settle_recipient(state, &mut old_recipient)?;
+    let rewards = old_recipient.accrued.u128();
+    let mut fees = load_fees(&deps.storage)?;
+    let (mut messages, treasury_amount, burned_amount) = take_fees(&buttcoin, &mut fees, rewards)?;
+    fees_config(&mut deps.storage).save(&fees)?;
+    let paid_amount = amount_after_fees(rewards, treasury_amount, burned_amount)?;
+    messages.extend(delivery_messages(
+        &buttcoin,
+        &old_recipient.delivery_mode,
+        old.clone(),
+        old_recipient.contract.contract_hash.clone(),
+        paid_amount,
+        None,
+    )?);
+
+    let weight = old_recipient.weight;
+    change_recipient_weight(state, &mut old_recipient, Uint128(0))?;
+    register_recipient(
+        deps,
+        state,
+        new.clone(),
+        weight,
+        old_recipient.delivery_mode,
+    )?;
+    let new_key = new_address.0.as_bytes();
+    let mut new_recipient = recipients_read(&deps.storage).load(new_key)?;
+    new_recipient.hook_variants = old_recipient.hook_variants;
+    recipients(&mut deps.storage).save(new_key, &new_recipient)?;
+    recipients(&mut deps.storage).remove(old_key);
+    replaced_recipients(&mut deps.storage).save(old_key, &new_address)?;
+
+    // register_recipient appended the new recipient, so it takes the old one's place instead
+    let mut addresses = recipient_addresses_read(&deps.storage).load()?;
+    addresses.retain(|address| *address != new_address);
+    for address in addresses.iter_mut() {
+        if *address == old {
+            *address = new_address.clone();
+        }
+    }
+    recipient_addresses(&mut deps.storage).save(&addresses)?;
+
+    if let Some(mut gauge) = gauge_config_read(&deps.storage).may_load()? {
+        for gauge_weight in gauge.tally.iter_mut() {
+            if gauge_weight.recipient == old {
+                gauge_weight.recipient = new_address.clone();
+            }
+        }
+        gauge_config(&mut deps.storage).save(&gauge)?;
+    }
+    let replaces_receivable_smart_contract = match &state.receivable_smart_contract {
+        Some(receivable_smart_contract) => {
+            deps.api.human_address(&receivable_smart_contract.address)? == old
+        }
+        None => false,
+    };
+    if replaces_receivable_smart_contract {
+        state.receivable_smart_contract = Some(new);
+    }
+
+    Ok((messages, paid_amount))
+}
+
+fn set_claim_limits<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    epoch_length: u64,
+    max_per_claim: Option<Uint128>,
+    max_per_epoch: Option<Uint128>,
+) -> StdResult<HandleResponse> {
+    if max_per_epoch.is_some() && epoch_length == 0 {
+        return Err(ContractError::MustBePositive {
+            field: "Epoch length",
+        }
+        .into());
+    }
+
+    claim_limits_config(&mut deps.storage).save(&ClaimLimits {
+        epoch_length,
+        max_per_claim,
+        max_per_epoch,
+    })?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::SetClaimLimits { status: Success },
+        )?),
+    })
+}
+
+fn set_distribution<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    hook: Option<Binary>,
+    max_recipients_per_call: u64,
+) -> StdResult<HandleResponse> {
+    if max_recipients_per_call == 0 {
+        return Err(ContractError::MustBePositive {
+            field: "Max recipients per call",
+        }
+        .into());
+    }
+
+    let mut distribution = load_distribution(&deps.storage)?;
+    distribution.hook = hook;
+    distribution.max_recipients_per_call = max_recipients_per_call;
+    distribution_config(&mut deps.storage).save(&distribution)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::SetDistribution { status: Success },
+        )?),
+    })
+}
+
+fn set_fees<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    burn_basis_points: u16,
+    treasury: Option<HumanAddr>,
+    treasury_basis_points: u16,
+) -> StdResult<HandleResponse> {
+    if burn_basis_points as u32 + treasury_basis_points as u32 > MAX_BASIS_POINTS as u32 {
+        return Err(ContractError::TooManyBasisPoints { subject: "Fees" }.into());
+    }
+    if treasury_basis_points > 0 && treasury.is_none() {
+        return Err(ContractError::TreasuryRequired.into());
+    }
+    let treasury = treasury
+        .map(|treasury| validate_address(&deps.api, &treasury))
+        .transpose()?;
+
+    let mut fees = load_fees(&deps.storage)?;
+    fees.burn_basis_points = burn_basis_points;
+    fees.treasury = treasury;
+    fees.treasury_basis_points = treasury_basis_points;
+    fees_config(&mut deps.storage).save(&fees)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::SetFees {
+            status: Success,
+        })?),
+    })
+}
+
+fn set_hook_validation<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    max_size: u64,
+    strict: bool,
+) -> StdResult<HandleResponse> {
+    // A max size of zero would reject every hook
+    if strict && max_size == 0 {
+        return Err(ContractError::MustBePositive {
+            field: "Max hook size",
+        }
+        .into());
+    }
+
+    hook_validation_config(&mut deps.storage).save(&HookValidation { max_size, strict })?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::SetHookValidation { status: Success },
+        )?),
+    })
+}
+
+fn set_keeper<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    bounty: KeeperBounty,
+    min_interval: u64,
+) -> StdResult<HandleResponse> {
+    if let KeeperBounty::BasisPoints { basis_points, .. } = bounty {
+        if basis_points > MAX_BASIS_POINTS {
+            return Err(ContractError::BasisPointsOutOfRange.into());
+        }
+    }
+
+    let keeper = match keeper_config_read(&deps.storage).may_load()? {
+        Some(keeper) => Keeper {
+            bounty,
+            min_interval,
+            ..keeper
+        },
+        None => Keeper {
+            bounty,
+            last_paid_block: None,
+            min_interval,
+            paid_cranks: 0,
+            total_paid: Uint128(0),
+        },
+    };
+    keeper_config(&mut deps.storage).save(&keeper)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::SetKeeper {
+            status: Success,
+        })?),
+    })
+}
+
+// Changing the signers invalidates every pending proposal.
+// Passing an empty list of signers hands control back to the admin.
+fn set_multisig<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    signers: Vec<HumanAddr>,
+    threshold: u64,
+    proposal_expiry: u64,
+) -> StdResult<HandleResponse> {
+    let signers = signers
+        .iter()
+        .map(|signer| validate_address(&deps.api, signer))
+        .collect::<StdResult<Vec<HumanAddr>>>()?;
+    for (i, signer) in signers.iter().enumerate() {
+        if signers[..i].contains(signer) {
+            return Err(ContractError::DuplicateSigner.into());
+        }
+    }
+    if !signers.is_empty() && (threshold == 0 || threshold > signers.len() as u64) {
+        return Err(ContractError::InvalidThreshold.into());
+    }
+    if !signers.is_empty() && proposal_expiry == 0 {
+        return Err(ContractError::MustBePositive {
+            field: "Proposal expiry",
+        }
+        .into());
+    }
+
+    let proposal_count = match multisig_config_read(&deps.storage).may_load()? {
+        Some(multisig) => multisig.proposal_count,
+        None => 0,
+    };
+    multisig_config(&mut deps.storage).save(&Multisig {
+        pending_proposal_ids: vec![],
+        proposal_count,
+        proposal_expiry,
+        signers,
+        threshold,
+    })?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::SetMultisig {
+            status: Success,
+        })?),
+    })
+}
+
+// Registers the receivable smart contract as a recipient with a weight of 1, once it accepts.
+fn set_receivable_smart_contract<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    receivable_smart_contract: SecretContract,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    if state.receivable_smart_contract.is_some() {
+        return Err(ContractError::AlreadySet {
+            setting: "Receivable smart contract",
+        }
+        .into());
+    }
+    let canonical_receivable_smart_contract =
+        canonicalize_contract(&deps.api, &receivable_smart_contract)?;
+
+    register_pending_recipient(
+        deps,
+        receivable_smart_contract,
+        Uint128(1),
+        DeliveryMode::default(),
+        None,
+    )?;
+    state.receivable_smart_contract = Some(canonical_receivable_smart_contract);
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::SetReceivableSmartContract { status: Success },
+        )?),
+    })
+}
+
+fn set_recipient_hook_variants<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    address: HumanAddr,
+    hook_variants: Option<Vec<String>>,
+) -> StdResult<HandleResponse> {
+    let mut recipient = match recipients_read(&deps.storage).may_load(address.0.as_bytes())? {
+        Some(recipient) => recipient,
+        None => return Err(ContractError::NotRecipient { address }.into()),
+    };
+    recipient.hook_variants = hook_variants;
+    recipients(&mut deps.storage).save(address.0.as_bytes(), &recipient)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::SetRecipientHookVariants { status: Success },
+        )?),
+    })
+}
+
+fn set_recipient_weight<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    address: HumanAddr,
+    weight: Uint128,
+) -> StdResult<HandleResponse> {
+    if gauge_config_read(&deps.storage).may_load()?.is_some() {
+        return Err(ContractError::WeightsSetByGauge.into());
+    }
+
+    let mut state = config_read(&deps.storage).load()?;
+    let mut recipient = match recipients_read(&deps.storage).may_load(address.0.as_bytes())? {
+        Some(recipient) => recipient,
+        None => return Err(ContractError::NotRecipient { address }.into()),
+    };
+    validate_total_weight(&state, recipient.weight, weight)?;
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    change_recipient_weight(&mut state, &mut recipient, weight)?;
+    recipients(&mut deps.storage).save(address.0.as_bytes(), &recipient)?;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::SetRecipientWeight { status: Success },
+        )?),
+    })
+}
+
+// Expiry is counted from when it is turned on, rather than applying to what was earned before.
+fn set_reward_expiry<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    bucket_length: u64,
+    destination: ExpiryDestination,
+    expiry_blocks: Option<u64>,
+) -> StdResult<HandleResponse> {
+    if bucket_length == 0 {
+        return Err(ContractError::MustBePositive {
+            field: "Bucket length",
+        }
+        .into());
+    }
+    let destination = match destination {
+        ExpiryDestination::Treasury { address } => ExpiryDestination::Treasury {
+            address: validate_address(&deps.api, &address)?,
+        },
+        destination => destination,
+    };
+
+    let previous_reward_expiry = reward_expiry_config_read(&deps.storage).may_load()?;
+    let counted_from_block = match &previous_reward_expiry {
+        Some(reward_expiry) if reward_expiry.expiry_blocks.is_some() => {
+            reward_expiry.counted_from_block
+        }
+        _ => {
+            if expiry_blocks.is_some() {
+                set_aside_unexpiring_rewards(deps, env.block.height)?;
+            }
+            env.block.height
+        }
+    };
+    let total_expired =
+        previous_reward_expiry.map_or(Uint128(0), |reward_expiry| reward_expiry.total_expired);
+    reward_expiry_config(&mut deps.storage).save(&RewardExpiry {
+        bucket_length,
+        counted_from_block,
+        destination,
+        expiry_blocks,
+        total_expired,
+    })?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(
+            &ButtcoinDistributorHandleAnswer::SetRewardExpiry { status: Success },
+        )?),
+    })
+}
+
+// Buttcoin earned while expiry was off never expires, so everything that hasn't been put in buckets
+// by the time it is turned on is set aside.
+fn set_aside_unexpiring_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    block: u64,
+) -> StdResult<()> {
+    let mut state = config_read(&deps.storage).load()?;
+    update_rewards(&mut deps.storage, &mut state, block)?;
+    for address in recipient_addresses_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+    {
+        let key = address.0.as_bytes();
+        let mut recipient = recipients_read(&deps.storage).load(key)?;
+        settle_recipient(&mut state, &mut recipient)?;
+        let bucketed = recipient
+            .accrual_buckets
+            .iter()
+            .try_fold(0, |bucketed, bucket| {
+                checked_add(bucketed, bucket.amount.u128(), "expiring rewards")
+            })?;
+        recipient.unexpiring = Uint128(recipient.accrued.u128().saturating_sub(bucketed));
+        recipient.bucketed_until_block = block;
+        recipients(&mut deps.storage).save(key, &recipient)?;
+    }
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(())
+}
+
+fn set_schedule<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    end_block: u64,
+    max_total_emission: Option<Uint128>,
+    release_per_block: Uint128,
+    release_per_block_denominator: Option<u64>,
+) -> StdResult<HandleResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    let release_per_block_denominator = validate_schedule(
+        state.starting_block,
+        end_block,
+        release_per_block,
+        release_per_block_denominator,
+    )?;
+
+    update_rewards(&mut deps.storage, &mut state, env.block.height)?;
+    if let Some(max_total_emission) = max_total_emission {
+        if max_total_emission < state.total_emitted {
+            return Err(ContractError::InvalidSchedule {
+                reason: "Max total emission can not be less than what has already been emitted.",
+            }
+            .into());
+        }
+        validate_max_total_emission(max_total_emission)?;
+        state.max_total_emission = max_total_emission;
+    }
+    state.end_block = end_block;
+    state.release_per_block = release_per_block;
+    state.release_per_block_denominator = release_per_block_denominator;
+    state.schedule_checkpoint_block = state.last_update_block;
+    config(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&ButtcoinDistributorHandleAnswer::SetSchedule {
+            status: Success,
+        })?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::error_code;
+    use crate::msg::YieldOptimizerDepositButtcoinHookMsg;
+    use crate::state::SecretContract;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{from_binary, CosmosMsg, HumanAddr, WasmMsg};
+    use serde::Deserialize;
+
+    // === CONSTANTS ===
+    pub const MOCK_SMART_CONTRACT_INITIALIZER: &str = "smart_contract_initializer";
+
+    // Only the fields needed to add up what the contract pays out
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Snip20HandleMsg {
+        Send { amount: Uint128 },
+        Transfer { amount: Uint128 },
+    }
+
+    // === HELPERS ===
+    fn init_helper() -> (
+        StdResult<InitResponse>,
+        Extern<MockStorage, MockApi, MockQuerier>,
+    ) {
+        init_helper_with_schedule(122, 123, Uint128(34))
+    }
+
+    fn init_helper_with_schedule(
+        starting_block: u64,
+        end_block: u64,
+        release_per_block: Uint128,
+    ) -> (
+        StdResult<InitResponse>,
+        Extern<MockStorage, MockApi, MockQuerier>,
+    ) {
+        let env = mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]);
+        let mut deps = mock_dependencies(20, &[]);
+        let msg = InitMsg {
+            buttcoin: mock_buttcoin(),
+            end_block,
+            max_total_emission: None,
+            release_per_block,
+            release_per_block_denominator: None,
+            starting_block,
+            viewing_key: mock_viewing_key(),
+        };
+        (init(&mut deps, env.clone(), msg), deps)
+    }
+
+    fn paid_out_helper(messages: &[CosmosMsg]) -> u128 {
+        messages
+            .iter()
+            .map(|message| match message {
+                CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_binary(msg).unwrap() {
+                    Snip20HandleMsg::Send { amount } | Snip20HandleMsg::Transfer { amount } => {
+                        amount.u128()
+                    }
+                },
+                _ => panic!("unexpected message"),
+            })
+            .sum()
+    }
+
+    fn add_recipient_helper(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        recipient: SecretContract,
+        weight: u128,
+        height: u64,
+    ) {
+        handle(
+            deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, height),
+            ButtcoinDistributorHandleMsg::AddRecipient {
+                delivery_mode: None,
+                recipient: recipient.clone(),
+                weight: Uint128(weight),
+            },
+        )
+        .unwrap();
+        accept_recipient_helper(deps, recipient, height);
+    }
+
+    fn accept_recipient_helper(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        recipient: SecretContract,
+        height: u64,
+    ) {
+        handle(
+            deps,
+            mock_env_at_block(recipient.address.as_str(), height),
+            ButtcoinDistributorHandleMsg::AcceptRecipient {
+                contract_hash: recipient.contract_hash,
+            },
+        )
+        .unwrap();
+    }
+
+    fn lock_buttcoin_helper(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        voter: &str,
+        amount: u128,
+        height: u64,
+    ) {
+        handle(
+            deps,
+            mock_env_at_block(mock_buttcoin().address.as_str(), height),
+            ButtcoinDistributorHandleMsg::Receive {
+                sender: HumanAddr::from(voter),
+                from: HumanAddr::from(voter),
+                amount: Uint128(amount),
+                msg: Some(to_binary(&ButtcoinDistributorReceiveMsg::LockButtcoin {}).unwrap()),
+            },
+        )
+        .unwrap();
+    }
+
+    fn fund_helper(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        amount: u128,
+        height: u64,
+    ) {
+        handle(
+            deps,
+            mock_env_at_block(mock_buttcoin().address.as_str(), height),
+            ButtcoinDistributorHandleMsg::Receive {
+                sender: HumanAddr::from("funder"),
+                from: HumanAddr::from("funder"),
+                amount: Uint128(amount),
+                msg: None,
+            },
+        )
+        .unwrap();
+    }
+
+    fn pending_helper(
+        deps: &Extern<MockStorage, MockApi, MockQuerier>,
+        recipient: SecretContract,
+        block: u64,
+    ) -> Uint128 {
+        let res = from_binary(
+            &query(
+                deps,
+                ButtcoinDistributorQueryMsg::Pending {
+                    block,
+                    recipient: Some(recipient.address),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Pending { amount } => amount,
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    fn mock_env_at_block(sender: &str, height: u64) -> Env {
+        let mut env = mock_env(sender, &[]);
+        env.block.height = height;
+        env
+    }
+
+    fn mock_buttcoin() -> SecretContract {
+        SecretContract {
+            address: HumanAddr::from("buttcoincontract"),
+            contract_hash: "b".repeat(64),
+        }
+    }
+
+    fn mock_lp_farm_smart_contract() -> SecretContract {
+        SecretContract {
+            address: HumanAddr::from("lpfarm"),
+            contract_hash: "d".repeat(64),
+        }
     }
 
     fn mock_viewing_key() -> String {
         "viewing_key".to_string()
     }
 
-    fn mock_yield_optimizer_smart_contract() -> SecretContract {
-        SecretContract {
-            address: HumanAddr::from("yieldoptimizersmartcontractaddress"),
-            contract_hash: "yieldoptimizersmartcontracthash".to_string(),
-        }
-    }
+    fn mock_yield_optimizer_smart_contract() -> SecretContract {
+        SecretContract {
+            address: HumanAddr::from("yieldoptimizer"),
+            contract_hash: "c".repeat(64),
+        }
+    }
+
+    // === INIT ===
+
+    #[test]
+    fn test_init() {
+        let init_msg = InitMsg {
+            buttcoin: mock_buttcoin(),
+            end_block: 1_000,
+            max_total_emission: None,
+            release_per_block: Uint128(100),
+            release_per_block_denominator: None,
+            starting_block: 100,
+            viewing_key: mock_viewing_key(),
+        };
+        let init_error_helper = |msg: InitMsg| {
+            init(
+                &mut mock_dependencies(20, &[]),
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                msg,
+            )
+            .unwrap_err()
+        };
+
+        // = When the Buttcoin address is not valid
+        // = * It raises an error
+        let mut msg = init_msg.clone();
+        msg.buttcoin.address = HumanAddr::from("bc");
+        assert_eq!(
+            init_error_helper(msg),
+            StdError::from(ContractError::InvalidAddress {
+                address: HumanAddr::from("bc")
+            })
+        );
+
+        // = When the Buttcoin contract hash is not 64 hexadecimal characters
+        // = * It raises an error
+        for contract_hash in &["buttcoincontracthash", &"b".repeat(62), &"g".repeat(64)] {
+            let mut msg = init_msg.clone();
+            msg.buttcoin.contract_hash = contract_hash.to_string();
+            assert_eq!(
+                init_error_helper(msg),
+                StdError::from(ContractError::InvalidContractHash {
+                    contract_hash: contract_hash.to_string()
+                })
+            );
+        }
+
+        // = When the end block is not after the starting block
+        // = * It raises an error
+        for end_block in &[100, 99] {
+            let mut msg = init_msg.clone();
+            msg.end_block = *end_block;
+            assert_eq!(
+                init_error_helper(msg),
+                StdError::from(ContractError::InvalidSchedule {
+                    reason: "End block must be greater than the starting block."
+                })
+            );
+        }
+
+        // = When nothing is released per block
+        // = * It raises an error
+        let mut msg = init_msg.clone();
+        msg.release_per_block = Uint128(0);
+        assert_eq!(
+            init_error_helper(msg),
+            StdError::from(ContractError::MustBePositive {
+                field: "Release per block"
+            })
+        );
+
+        // = When the release per block denominator is zero
+        // = * It raises an error
+        let mut msg = init_msg.clone();
+        msg.release_per_block_denominator = Some(0);
+        assert_eq!(
+            init_error_helper(msg),
+            StdError::from(ContractError::MustBePositive {
+                field: "Release per block denominator"
+            })
+        );
+
+        // = When the max total emission is zero
+        // = * It raises an error
+        let mut msg = init_msg.clone();
+        msg.max_total_emission = Some(Uint128(0));
+        assert_eq!(
+            init_error_helper(msg),
+            StdError::from(ContractError::MustBePositive {
+                field: "Max total emission"
+            })
+        );
+
+        // = When everything is valid
+        // = * It stores the canonical Buttcoin address
+        let mut deps = mock_dependencies(20, &[]);
+        init(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            init_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            config_read(&deps.storage).load().unwrap().buttcoin,
+            CanonicalSecretContract {
+                address: deps
+                    .api
+                    .canonical_address(&mock_buttcoin().address)
+                    .unwrap(),
+                contract_hash: mock_buttcoin().contract_hash,
+            }
+        );
+    }
+
+    // === QUERY ===
+
+    #[test]
+    fn test_query_config() {
+        let (_init_result, deps) = init_helper();
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Config {}).unwrap()).unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Config {
+                admin,
+                buttcoin,
+                emission_headroom,
+                end_block,
+                frozen,
+                last_update_block,
+                max_total_emission,
+                receivable_smart_contract,
+                release_per_block,
+                release_per_block_denominator,
+                starting_block,
+                total_funded,
+                viewing_key,
+            } => {
+                assert_eq!(admin, HumanAddr::from(MOCK_SMART_CONTRACT_INITIALIZER));
+                assert_eq!(buttcoin, mock_buttcoin());
+                assert_eq!(emission_headroom, Uint128(34));
+                assert_eq!(end_block, 123);
+                assert!(!frozen);
+                assert_eq!(last_update_block, 122);
+                assert_eq!(max_total_emission, Uint128(34));
+                assert_eq!(receivable_smart_contract, None);
+                assert_eq!(release_per_block, Uint128(34));
+                assert_eq!(release_per_block_denominator, 1);
+                assert_eq!(starting_block, 122);
+                assert_eq!(total_funded, Uint128(0));
+                assert_eq!(viewing_key, mock_viewing_key());
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_max_total_emission() {
+        let mut deps = mock_dependencies(20, &[]);
+        init(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            InitMsg {
+                buttcoin: mock_buttcoin(),
+                end_block: 1_000,
+                max_total_emission: Some(Uint128(1_000)),
+                release_per_block: Uint128(100),
+                release_per_block_denominator: None,
+                starting_block: 100,
+                viewing_key: mock_viewing_key(),
+            },
+        )
+        .unwrap();
+        let headroom_helper = |deps: &Extern<MockStorage, MockApi, MockQuerier>| match from_binary(
+            &query(deps, ButtcoinDistributorQueryMsg::Config {}).unwrap(),
+        )
+        .unwrap()
+        {
+            ButtcoinDistributorQueryAnswer::Config {
+                emission_headroom, ..
+            } => emission_headroom.u128(),
+            _ => panic!("unexpected error"),
+        };
+        let extra_recipient = |i: u64| SecretContract {
+            address: HumanAddr::from(format!("extra{}", i)),
+            contract_hash: "e".repeat(64),
+        };
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 3, 100);
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetKeeper {
+                bounty: KeeperBounty::Fixed { amount: Uint128(7) },
+                min_interval: 0,
+            },
+        )
+        .unwrap();
+
+        // = When claiming before the cap is reached
+        // = * It reduces the headroom by what was released
+        assert_eq!(headroom_helper(&deps), 1_000);
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block(mock_lp_farm_smart_contract().address.as_str(), 104),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 300);
+        assert_eq!(headroom_helper(&deps), 600);
+
+        // = When claims, cranks and configuration changes go past the cap
+        // = * It never pays out more than the cap
+        let mut paid_out: u128 = 300;
+        let mut block: u64 = 104;
+        let mut seed: u64 = 42;
+        let mut extra_recipients: u64 = 0;
+        for _ in 0..500 {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            block += (seed >> 33) % 3;
+            let handle_result = match (seed >> 40) % 6 {
+                0 => handle(
+                    &mut deps,
+                    mock_env_at_block(
+                        mock_yield_optimizer_smart_contract().address.as_str(),
+                        block,
+                    ),
+                    ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                        amount: None,
+                        hook: None,
+                        recipient: None,
+                    },
+                ),
+                1 => handle(
+                    &mut deps,
+                    mock_env_at_block(mock_lp_farm_smart_contract().address.as_str(), block),
+                    ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                        amount: None,
+                        hook: None,
+                        recipient: None,
+                    },
+                ),
+                2 => handle(
+                    &mut deps,
+                    mock_env_at_block("keeper", block),
+                    ButtcoinDistributorHandleMsg::Distribute {},
+                ),
+                3 => handle(
+                    &mut deps,
+                    mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, block),
+                    ButtcoinDistributorHandleMsg::SetSchedule {
+                        end_block: 2_000,
+                        max_total_emission: None,
+                        release_per_block: Uint128((seed >> 20) as u128 % 1_000),
+                        release_per_block_denominator: None,
+                    },
+                ),
+                4 => handle(
+                    &mut deps,
+                    mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, block),
+                    ButtcoinDistributorHandleMsg::SetRecipientWeight {
+                        address: mock_yield_optimizer_smart_contract().address,
+                        weight: Uint128((seed >> 24) as u128 % 7),
+                    },
+                ),
+                _ => {
+                    extra_recipients += 1;
+                    handle(
+                        &mut deps,
+                        mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, block),
+                        ButtcoinDistributorHandleMsg::AddRecipient {
+                            delivery_mode: None,
+                            recipient: extra_recipient(extra_recipients),
+                            weight: Uint128(5),
+                        },
+                    )
+                    .unwrap();
+                    handle(
+                        &mut deps,
+                        mock_env_at_block(
+                            extra_recipient(extra_recipients).address.as_str(),
+                            block,
+                        ),
+                        ButtcoinDistributorHandleMsg::AcceptRecipient {
+                            contract_hash: extra_recipient(extra_recipients).contract_hash,
+                        },
+                    )
+                }
+            }
+            .unwrap();
+            paid_out += paid_out_helper(&handle_result.messages);
+            assert!(paid_out <= 1_000);
+        }
+        assert_eq!(headroom_helper(&deps), 0);
+
+        // == When everyone has been paid out afterwards
+        // == * It has paid out at most the cap
+        let mut claimers = vec![
+            mock_yield_optimizer_smart_contract().address,
+            mock_lp_farm_smart_contract().address,
+        ];
+        claimers.extend((1..=extra_recipients).map(|i| extra_recipient(i).address));
+        for claimer in claimers {
+            let handle_result = handle(
+                &mut deps,
+                mock_env_at_block(claimer.as_str(), block + 100),
+                ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                    amount: None,
+                    hook: None,
+                    recipient: None,
+                },
+            )
+            .unwrap();
+            paid_out += paid_out_helper(&handle_result.messages);
+        }
+        assert!(paid_out <= 1_000);
+    }
+
+    #[test]
+    fn test_fractional_release_per_block() {
+        let mut deps = mock_dependencies(20, &[]);
+        init(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            InitMsg {
+                buttcoin: mock_buttcoin(),
+                end_block: 100 + 14_400,
+                max_total_emission: None,
+                release_per_block: Uint128(1_000),
+                release_per_block_denominator: Some(14_400),
+                starting_block: 100,
+                viewing_key: mock_viewing_key(),
+            },
+        )
+        .unwrap();
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+
+        // = When less than a whole Buttcoin has been released
+        // = * It keeps the fraction for later
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 114),
+            Uint128(0)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 115),
+            Uint128(1)
+        );
+
+        // = When claiming every few blocks
+        // = * It pays out exactly what the schedule releases
+        let mut paid_out: u128 = 0;
+        for block in (107..=100 + 14_400).step_by(7) {
+            let handle_result = handle(
+                &mut deps,
+                mock_env_at_block(
+                    mock_yield_optimizer_smart_contract().address.as_str(),
+                    block,
+                ),
+                ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                    amount: None,
+                    hook: None,
+                    recipient: None,
+                },
+            )
+            .unwrap();
+            paid_out += paid_out_helper(&handle_result.messages);
+            assert_eq!(paid_out, (block - 100) as u128 * 1_000 / 14_400);
+        }
+    }
+
+    #[test]
+    fn test_rounding_remainders() {
+        let claimers = [
+            mock_yield_optimizer_smart_contract(),
+            mock_lp_farm_smart_contract(),
+            SecretContract {
+                address: HumanAddr::from("thirdcontract"),
+                contract_hash: "e".repeat(64),
+            },
+        ];
+
+        // = When any release rate is split between recipients of any weights
+        // = * It pays out exactly what was released once everyone has claimed
+        for &denominator in &[1u64, 2, 3, 7, 14_400] {
+            for &rate in &[1u128, 2, 5, 13, 1_000] {
+                for weights in 0..27u128 {
+                    let weights = [1 + weights % 3, 1 + weights / 3 % 3, 1 + weights / 9];
+                    let mut deps = mock_dependencies(20, &[]);
+                    init(
+                        &mut deps,
+                        mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                        InitMsg {
+                            buttcoin: mock_buttcoin(),
+                            end_block: 120,
+                            max_total_emission: None,
+                            release_per_block: Uint128(rate),
+                            release_per_block_denominator: Some(denominator),
+                            starting_block: 100,
+                            viewing_key: mock_viewing_key(),
+                        },
+                    )
+                    .unwrap();
+                    for (claimer, weight) in claimers.iter().zip(weights.iter()) {
+                        add_recipient_helper(&mut deps, claimer.clone(), *weight, 100);
+                    }
+
+                    let mut paid_out: u128 = 0;
+                    for block in 100..=121u64 {
+                        for (i, claimer) in claimers.iter().enumerate() {
+                            if block % (i as u64 + 2) != 0 && block != 121 {
+                                continue;
+                            }
+                            let handle_result = handle(
+                                &mut deps,
+                                mock_env_at_block(claimer.address.as_str(), block),
+                                ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                                    amount: None,
+                                    hook: None,
+                                    recipient: None,
+                                },
+                            )
+                            .unwrap();
+                            paid_out += paid_out_helper(&handle_result.messages);
+                        }
+                    }
+
+                    let state = config_read(&deps.storage).load().unwrap();
+                    let scheduled = 20 * rate / denominator as u128;
+                    assert_eq!(state.total_emitted.u128(), scheduled);
+                    assert_eq!(paid_out, scheduled);
+                    assert_eq!(state.reward_remainder, Uint128(0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_values() {
+        // = When the release up to the end block doesn't fit
+        // = * It refuses the schedule
+        let schedule_error = StdError::from(ContractError::InvalidSchedule {
+            reason: "Release per block is too large for the length of the schedule.",
+        });
+        let (init_result, _deps) = init_helper_with_schedule(0, u64::MAX, Uint128(u128::MAX));
+        assert_eq!(init_result.unwrap_err(), schedule_error);
+        let mut deps = mock_dependencies(20, &[]);
+        let init_result = init(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            InitMsg {
+                buttcoin: mock_buttcoin(),
+                end_block: u64::MAX,
+                max_total_emission: Some(Uint128(1)),
+                release_per_block: Uint128(u128::MAX),
+                release_per_block_denominator: None,
+                starting_block: 0,
+                viewing_key: mock_viewing_key(),
+            },
+        );
+        assert_eq!(init_result.unwrap_err(), schedule_error);
+
+        // = When the emission could overflow the reward math
+        // = * It refuses the schedule
+        let emission_error = StdError::from(ContractError::InvalidSchedule {
+            reason: "Max total emission is too large for the reward math.",
+        });
+        let (init_result, mut deps) =
+            init_helper_with_schedule(0, 10, Uint128((MAX_TOTAL_EMISSION + 1) / 10 + 1));
+        assert_eq!(init_result.unwrap_err(), emission_error);
+        init(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            InitMsg {
+                buttcoin: mock_buttcoin(),
+                end_block: 10,
+                max_total_emission: Some(Uint128(MAX_TOTAL_EMISSION)),
+                release_per_block: Uint128(MAX_TOTAL_EMISSION / 10 + 1),
+                release_per_block_denominator: None,
+                starting_block: 0,
+                viewing_key: mock_viewing_key(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 0),
+                ButtcoinDistributorHandleMsg::SetSchedule {
+                    end_block: 10,
+                    max_total_emission: Some(Uint128(MAX_TOTAL_EMISSION + 1)),
+                    release_per_block: Uint128(1),
+                    release_per_block_denominator: None,
+                }
+            )
+            .unwrap_err(),
+            emission_error
+        );
+
+        // = When the recipient weights add up to more than the reward math is sized for
+        // = * It refuses the weight
+        let weight_error = StdError::from(ContractError::TooMuchWeight);
+        add_recipient_helper(
+            &mut deps,
+            mock_yield_optimizer_smart_contract(),
+            MAX_TOTAL_WEIGHT - 1,
+            0,
+        );
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 0),
+                ButtcoinDistributorHandleMsg::AddRecipient {
+                    delivery_mode: None,
+                    recipient: mock_lp_farm_smart_contract(),
+                    weight: Uint128(2),
+                }
+            )
+            .unwrap_err(),
+            weight_error
+        );
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 0),
+                ButtcoinDistributorHandleMsg::SetRecipientWeight {
+                    address: mock_yield_optimizer_smart_contract().address,
+                    weight: Uint128(MAX_TOTAL_WEIGHT + 1),
+                }
+            )
+            .unwrap_err(),
+            weight_error
+        );
+
+        // = When the largest emission is spread over the largest weights
+        // = * It pays out everything without overflowing
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 0);
+        let claim_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount: None,
+            hook: None,
+            recipient: None,
+        };
+        let mut paid_out = 0;
+        for recipient in &[
+            mock_yield_optimizer_smart_contract(),
+            mock_lp_farm_smart_contract(),
+        ] {
+            let res = handle(
+                &mut deps,
+                mock_env_at_block(recipient.address.as_str(), 10),
+                claim_msg.clone(),
+            )
+            .unwrap();
+            paid_out += paid_out_helper(&res.messages);
+        }
+        assert_eq!(paid_out, MAX_TOTAL_EMISSION);
+
+        // = When handle and query are given extreme schedules, weights, amounts and blocks
+        // = * It answers or raises an error but never panics
+        let recipient = mock_yield_optimizer_smart_contract();
+        for &release_per_block in &[
+            1,
+            u64::MAX as u128,
+            u128::MAX / REWARD_PRECISION,
+            u128::MAX / 2,
+            u128::MAX,
+        ] {
+            for &release_per_block_denominator in &[1, u64::MAX] {
+                for &starting_block in &[0, u64::MAX - 1] {
+                    for &weight in &[1, MAX_TOTAL_WEIGHT, u128::MAX] {
+                        let mut deps = mock_dependencies(20, &[]);
+                        let init_result = init(
+                            &mut deps,
+                            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 0),
+                            InitMsg {
+                                buttcoin: mock_buttcoin(),
+                                end_block: u64::MAX,
+                                max_total_emission: Some(Uint128(MAX_TOTAL_EMISSION)),
+                                release_per_block: Uint128(release_per_block),
+                                release_per_block_denominator: Some(release_per_block_denominator),
+                                starting_block,
+                                viewing_key: mock_viewing_key(),
+                            },
+                        );
+                        if init_result.is_err() {
+                            continue;
+                        }
+                        let admin_msgs = vec![
+                            ButtcoinDistributorHandleMsg::AddRecipient {
+                                delivery_mode: None,
+                                recipient: recipient.clone(),
+                                weight: Uint128(weight),
+                            },
+                            ButtcoinDistributorHandleMsg::SetFees {
+                                burn_basis_points: 100,
+                                treasury: Some(HumanAddr::from("treasury")),
+                                treasury_basis_points: 100,
+                            },
+                            ButtcoinDistributorHandleMsg::SetKeeper {
+                                bounty: KeeperBounty::BasisPoints {
+                                    basis_points: 100,
+                                    cap: Uint128(u128::MAX),
+                                },
+                                min_interval: u64::MAX,
+                            },
+                            ButtcoinDistributorHandleMsg::SetRewardExpiry {
+                                bucket_length: 1,
+                                destination: ExpiryDestination::Redistribute {},
+                                expiry_blocks: Some(u64::MAX),
+                            },
+                        ];
+                        for msg in admin_msgs {
+                            let _ = handle(
+                                &mut deps,
+                                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 0),
+                                msg,
+                            );
+                        }
+                        let _ = handle(
+                            &mut deps,
+                            mock_env_at_block(recipient.address.as_str(), 0),
+                            ButtcoinDistributorHandleMsg::AcceptRecipient {
+                                contract_hash: recipient.contract_hash.clone(),
+                            },
+                        );
+                        for &block in &[0, 1, starting_block + 1, u64::MAX - 1, u64::MAX] {
+                            for msg in [
+                                ButtcoinDistributorHandleMsg::Distribute {},
+                                claim_msg.clone(),
+                                ButtcoinDistributorHandleMsg::ExpireRewards {
+                                    recipients: vec![recipient.address.clone()],
+                                },
+                                ButtcoinDistributorHandleMsg::SetRecipientWeight {
+                                    address: recipient.address.clone(),
+                                    weight: Uint128(weight),
+                                },
+                                ButtcoinDistributorHandleMsg::SetSchedule {
+                                    end_block: u64::MAX,
+                                    max_total_emission: None,
+                                    release_per_block: Uint128(release_per_block),
+                                    release_per_block_denominator: None,
+                                },
+                            ]
+                            .iter()
+                            .cloned()
+                            {
+                                let sender = match msg {
+                                    ButtcoinDistributorHandleMsg::ClaimButtcoin { .. } => {
+                                        recipient.address.as_str()
+                                    }
+                                    _ => MOCK_SMART_CONTRACT_INITIALIZER,
+                                };
+                                let _ = handle(&mut deps, mock_env_at_block(sender, block), msg);
+                            }
+                            let _ = query(
+                                &deps,
+                                ButtcoinDistributorQueryMsg::Pending {
+                                    block,
+                                    recipient: Some(recipient.address.clone()),
+                                },
+                            );
+                            let _ = query(
+                                &deps,
+                                ButtcoinDistributorQueryMsg::Pending {
+                                    block,
+                                    recipient: None,
+                                },
+                            );
+                            let _ = query(&deps, ButtcoinDistributorQueryMsg::Config {});
+                        }
+                        // Gauge voting with everything locked
+                        let _ = handle(
+                            &mut deps,
+                            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, u64::MAX),
+                            ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 1 },
+                        );
+                        let _ = handle(
+                            &mut deps,
+                            mock_env_at_block(mock_buttcoin().address.as_str(), u64::MAX),
+                            ButtcoinDistributorHandleMsg::Receive {
+                                sender: HumanAddr::from("voter"),
+                                from: HumanAddr::from("voter"),
+                                amount: Uint128(u128::MAX),
+                                msg: Some(
+                                    to_binary(&ButtcoinDistributorReceiveMsg::LockButtcoin {})
+                                        .unwrap(),
+                                ),
+                            },
+                        );
+                        let _ = handle(
+                            &mut deps,
+                            mock_env_at_block("voter", u64::MAX),
+                            ButtcoinDistributorHandleMsg::Vote {
+                                allocations: vec![VoteAllocation {
+                                    basis_points: MAX_BASIS_POINTS,
+                                    recipient: recipient.address.clone(),
+                                }],
+                            },
+                        );
+                        let _ = query(
+                            &deps,
+                            ButtcoinDistributorQueryMsg::Pending {
+                                block: u64::MAX,
+                                recipient: Some(recipient.address.clone()),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        // = When claim limits and reward expiry are given extreme values
+        // = * It answers or raises an error but never panics
+        // = * It never pays out more than has been emitted
+        for &(epoch_length, max_per_claim, max_per_epoch) in &[
+            (1, Some(1), Some(u128::MAX)),
+            (1, None, Some(u128::MAX)),
+            (u64::MAX, Some(u128::MAX), Some(1)),
+            (u64::MAX, Some(u128::MAX), Some(u128::MAX)),
+        ] {
+            for &(bucket_length, expiry_blocks) in
+                &[(1, 1), (1, u64::MAX), (u64::MAX, 1), (u64::MAX, u64::MAX)]
+            {
+                for &release_per_block in &[1_000, u64::MAX as u128] {
+                    let mut deps = mock_dependencies(20, &[]);
+                    init(
+                        &mut deps,
+                        mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 0),
+                        InitMsg {
+                            buttcoin: mock_buttcoin(),
+                            end_block: u64::MAX,
+                            max_total_emission: Some(Uint128(MAX_TOTAL_EMISSION)),
+                            release_per_block: Uint128(release_per_block),
+                            release_per_block_denominator: None,
+                            starting_block: 0,
+                            viewing_key: mock_viewing_key(),
+                        },
+                    )
+                    .unwrap();
+                    add_recipient_helper(&mut deps, recipient.clone(), 1, 0);
+                    for msg in [
+                        ButtcoinDistributorHandleMsg::SetClaimLimits {
+                            epoch_length,
+                            max_per_claim: max_per_claim.map(Uint128),
+                            max_per_epoch: max_per_epoch.map(Uint128),
+                        },
+                        ButtcoinDistributorHandleMsg::SetRewardExpiry {
+                            bucket_length,
+                            destination: ExpiryDestination::Treasury {
+                                address: HumanAddr::from("treasury"),
+                            },
+                            expiry_blocks: Some(expiry_blocks),
+                        },
+                    ]
+                    .iter()
+                    .cloned()
+                    {
+                        handle(
+                            &mut deps,
+                            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 0),
+                            msg,
+                        )
+                        .unwrap();
+                    }
+                    let mut paid_out: u128 = 0;
+                    for &block in &[1, 2, u64::MAX / 2, u64::MAX - 1, u64::MAX] {
+                        for msg in [
+                            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                                amount: Some(Uint128(u128::MAX)),
+                                hook: None,
+                                recipient: None,
+                            },
+                            claim_msg.clone(),
+                            ButtcoinDistributorHandleMsg::Distribute {},
+                            ButtcoinDistributorHandleMsg::ExpireRewards {
+                                recipients: vec![recipient.address.clone()],
+                            },
+                        ]
+                        .iter()
+                        .cloned()
+                        {
+                            if let Ok(handle_result) = handle(
+                                &mut deps,
+                                mock_env_at_block(recipient.address.as_str(), block),
+                                msg,
+                            ) {
+                                paid_out = paid_out
+                                    .checked_add(paid_out_helper(&handle_result.messages))
+                                    .unwrap();
+                            }
+                        }
+                        let _ = query(
+                            &deps,
+                            ButtcoinDistributorQueryMsg::ExpiringRewards {
+                                recipient: recipient.address.clone(),
+                            },
+                        );
+                    }
+                    assert!(
+                        paid_out
+                            <= config_read(&deps.storage)
+                                .load()
+                                .unwrap()
+                                .total_emitted
+                                .u128()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_pending_rewards() {
+        let (_init_result, mut deps) = init_helper();
+
+        // = When block specified is smaller or less than the last update block
+        // = * It returns 0
+        let res = from_binary(
+            &query(
+                &deps,
+                ButtcoinDistributorQueryMsg::Pending {
+                    block: 122,
+                    recipient: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Pending { amount } => {
+                assert_eq!(amount, Uint128(0));
+            }
+            _ => panic!("unexpected error"),
+        }
+
+        // = When block specified is greater than the last update block
+        // == When block specified is less than or equal to the end_block
+        // == * It returns the correct amount
+        let res = from_binary(
+            &query(
+                &deps,
+                ButtcoinDistributorQueryMsg::Pending {
+                    block: 123,
+                    recipient: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Pending { amount } => {
+                assert_eq!(amount, Uint128(34));
+            }
+            _ => panic!("unexpected error"),
+        }
+        // == When the block specified is more than the end_block
+        // == * It returns the correct amount
+        let res = from_binary(
+            &query(
+                &deps,
+                ButtcoinDistributorQueryMsg::Pending {
+                    block: 123,
+                    recipient: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Pending { amount } => {
+                assert_eq!(amount, Uint128(34));
+            }
+            _ => panic!("unexpected error"),
+        }
+
+        // = When the receivable smart contract has not accepted its registration yet
+        // = * It returns 0
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
+                receivable_smart_contract: mock_yield_optimizer_smart_contract(),
+            },
+        )
+        .unwrap();
+        let res = from_binary(
+            &query(
+                &deps,
+                ButtcoinDistributorQueryMsg::Pending {
+                    block: 123,
+                    recipient: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Pending { amount } => {
+                assert_eq!(amount, Uint128(0));
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_query_proposals() {
+        let (_init_result, mut deps) = init_helper();
+
+        // = When multisig is not enabled
+        // = * It returns an empty list
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Proposals {}).unwrap()).unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Proposals { proposals } => {
+                assert_eq!(proposals, vec![]);
+            }
+            _ => panic!("unexpected error"),
+        }
+
+        // = When there is a pending proposal
+        // = * It returns the pending proposal
+        let handle_msg = ButtcoinDistributorHandleMsg::SetMultisig {
+            signers: vec![HumanAddr::from("alice"), HumanAddr::from("bob")],
+            threshold: 2,
+            proposal_expiry: 10,
+        };
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            handle_msg,
+        )
+        .unwrap();
+        let change_admin_msg = ButtcoinDistributorHandleMsg::ChangeAdmin {
+            admin: HumanAddr::from("bob"),
+        };
+        handle(
+            &mut deps,
+            mock_env_at_block("alice", 5),
+            change_admin_msg.clone(),
+        )
+        .unwrap();
+        let expected_proposal = Proposal {
+            approvals: vec![HumanAddr::from("alice")],
+            executed: false,
+            expires_at_block: 15,
+            id: 1,
+            msg: change_admin_msg,
+        };
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Proposals {}).unwrap()).unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Proposals { proposals } => {
+                assert_eq!(proposals, vec![expected_proposal.clone()]);
+            }
+            _ => panic!("unexpected error"),
+        }
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Proposal { id: 1 }).unwrap())
+                .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Proposal { proposal } => {
+                assert_eq!(proposal, expected_proposal);
+            }
+            _ => panic!("unexpected error"),
+        }
+
+        // = When the proposal does not exist
+        // = * It raises an error
+        assert_eq!(
+            query(&deps, ButtcoinDistributorQueryMsg::Proposal { id: 2 }).unwrap_err(),
+            StdError::from(ContractError::ProposalNotFound { id: 2 })
+        );
+
+        // = When the proposal has been executed
+        // = * It is no longer listed but can still be queried by id
+        handle(
+            &mut deps,
+            mock_env_at_block("bob", 6),
+            ButtcoinDistributorHandleMsg::ApproveProposal { id: 1 },
+        )
+        .unwrap();
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Proposals {}).unwrap()).unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Proposals { proposals } => {
+                assert_eq!(proposals, vec![]);
+            }
+            _ => panic!("unexpected error"),
+        }
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Proposal { id: 1 }).unwrap())
+                .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Proposal { proposal } => {
+                assert!(proposal.executed);
+                assert_eq!(
+                    proposal.approvals,
+                    vec![HumanAddr::from("alice"), HumanAddr::from("bob")]
+                );
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_query_gauge_weights() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+
+        // = When gauge voting is not enabled
+        // = * It raises an error
+        assert_eq!(
+            query(
+                &deps,
+                ButtcoinDistributorQueryMsg::GaugeWeights { epoch: 0 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::GaugeVotingNotEnabled)
+        );
+
+        // = When gauge voting is enabled
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 115),
+            ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 10 },
+        )
+        .unwrap();
+        lock_buttcoin_helper(&mut deps, "voter", 300, 116);
+        handle(
+            &mut deps,
+            mock_env_at_block("voter", 116),
+            ButtcoinDistributorHandleMsg::Vote {
+                allocations: vec![VoteAllocation {
+                    basis_points: 10_000,
+                    recipient: mock_lp_farm_smart_contract().address,
+                }],
+            },
+        )
+        .unwrap();
+        let weights_for_epoch = |deps: &Extern<MockStorage, MockApi, MockQuerier>, epoch| {
+            let res = from_binary(
+                &query(deps, ButtcoinDistributorQueryMsg::GaugeWeights { epoch }).unwrap(),
+            )
+            .unwrap();
+            match res {
+                ButtcoinDistributorQueryAnswer::GaugeWeights { weights, .. } => weights,
+                _ => panic!("unexpected error"),
+            }
+        };
+        let admin_weights = vec![
+            GaugeWeight {
+                recipient: mock_yield_optimizer_smart_contract().address,
+                weight: Uint128(1),
+            },
+            GaugeWeight {
+                recipient: mock_lp_farm_smart_contract().address,
+                weight: Uint128(1),
+            },
+        ];
+
+        // == When the epoch is before gauge voting was enabled
+        // == * It raises an error
+        assert_eq!(
+            query(
+                &deps,
+                ButtcoinDistributorQueryMsg::GaugeWeights { epoch: 0 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::GaugeVotingNotEnabledInEpoch)
+        );
+
+        // == When the epoch is the one gauge voting was enabled in
+        // == * It returns the weights set by the admin
+        assert_eq!(weights_for_epoch(&deps, 1), admin_weights);
+
+        // == When the epoch has not been reached yet
+        // == * It returns the shares of the live tally
+        let voted_weights = vec![GaugeWeight {
+            recipient: mock_lp_farm_smart_contract().address,
+            weight: Uint128(MAX_TOTAL_WEIGHT),
+        }];
+        assert_eq!(weights_for_epoch(&deps, 2), voted_weights);
+
+        // == When the epoch has been applied
+        // == * It returns the weights applied at the epoch boundary
+        handle(
+            &mut deps,
+            mock_env_at_block("voter", 135),
+            ButtcoinDistributorHandleMsg::Vote {
+                allocations: vec![],
+            },
+        )
+        .unwrap();
+        let applied_weights = vec![
+            GaugeWeight {
+                recipient: mock_yield_optimizer_smart_contract().address,
+                weight: Uint128(0),
+            },
+            GaugeWeight {
+                recipient: mock_lp_farm_smart_contract().address,
+                weight: Uint128(MAX_TOTAL_WEIGHT),
+            },
+        ];
+        assert_eq!(weights_for_epoch(&deps, 1), admin_weights);
+        assert_eq!(weights_for_epoch(&deps, 2), applied_weights);
+        assert_eq!(weights_for_epoch(&deps, 3), applied_weights);
+        // === * It keeps the weights of the last epoch with votes when nobody votes
+        assert_eq!(weights_for_epoch(&deps, 10), applied_weights);
+    }
+
+    #[test]
+    fn test_query_votes() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+            ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 10 },
+        )
+        .unwrap();
+
+        // = When the voter has never locked Buttcoin
+        // = * It returns empty votes
+        let res = from_binary(
+            &query(
+                &deps,
+                ButtcoinDistributorQueryMsg::Votes {
+                    voter: HumanAddr::from("voter"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Votes {
+                allocations,
+                locked,
+                locked_in_epoch,
+            } => {
+                assert_eq!(allocations, vec![]);
+                assert_eq!(locked, Uint128(0));
+                assert_eq!(locked_in_epoch, 0);
+            }
+            _ => panic!("unexpected error"),
+        }
+
+        // = When the voter has locked Buttcoin and voted
+        // = * It returns the votes
+        lock_buttcoin_helper(&mut deps, "voter", 300, 125);
+        let allocations = vec![VoteAllocation {
+            basis_points: 2_500,
+            recipient: mock_yield_optimizer_smart_contract().address,
+        }];
+        handle(
+            &mut deps,
+            mock_env_at_block("voter", 125),
+            ButtcoinDistributorHandleMsg::Vote {
+                allocations: allocations.clone(),
+            },
+        )
+        .unwrap();
+        let res = from_binary(
+            &query(
+                &deps,
+                ButtcoinDistributorQueryMsg::Votes {
+                    voter: HumanAddr::from("voter"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Votes {
+                allocations: voter_allocations,
+                locked,
+                locked_in_epoch,
+            } => {
+                assert_eq!(voter_allocations, allocations);
+                assert_eq!(locked, Uint128(300));
+                assert_eq!(locked_in_epoch, 2);
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    // === HANDLE ===
+
+    #[test]
+    fn test_handle_add_recipient() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        let handle_msg = ButtcoinDistributorHandleMsg::AddRecipient {
+            delivery_mode: None,
+            recipient: mock_yield_optimizer_smart_contract(),
+            weight: Uint128(1),
+        };
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the recipient is not valid
+        // = * It raises an error
+        let add_recipient_error_helper =
+            |deps: &mut Extern<MockStorage, MockApi, MockQuerier>, recipient: SecretContract| {
+                handle(
+                    deps,
+                    mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                    ButtcoinDistributorHandleMsg::AddRecipient {
+                        delivery_mode: None,
+                        recipient,
+                        weight: Uint128(1),
+                    },
+                )
+                .unwrap_err()
+            };
+        assert_eq!(
+            add_recipient_error_helper(
+                &mut deps,
+                SecretContract {
+                    address: HumanAddr::from("yo"),
+                    contract_hash: mock_yield_optimizer_smart_contract().contract_hash,
+                }
+            ),
+            StdError::from(ContractError::InvalidAddress {
+                address: HumanAddr::from("yo")
+            })
+        );
+        assert_eq!(
+            add_recipient_error_helper(
+                &mut deps,
+                SecretContract {
+                    address: mock_yield_optimizer_smart_contract().address,
+                    contract_hash: "yieldoptimizerhash".to_string(),
+                }
+            ),
+            StdError::from(ContractError::InvalidContractHash {
+                contract_hash: "yieldoptimizerhash".to_string()
+            })
+        );
+
+        // = When called by the admin
+        // = * It splits the release between recipients by weight
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 3, 100);
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 110),
+            Uint128(250)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 110),
+            Uint128(750)
+        );
+
+        // == When the recipient is already registered
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+                handle_msg
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::AlreadyRegistered)
+        );
+
+        // == When a recipient claims
+        // == * It sends the recipient its share
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block(mock_lp_farm_smart_contract().address.as_str(), 110),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::send_msg(
+                mock_lp_farm_smart_contract().address,
+                Uint128(750),
+                Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook: None }).unwrap()),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 110),
+            Uint128(0)
+        );
+    }
+
+    #[test]
+    fn test_handle_accept_recipient() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+            ButtcoinDistributorHandleMsg::AddRecipient {
+                delivery_mode: None,
+                recipient: mock_yield_optimizer_smart_contract(),
+                weight: Uint128(1),
+            },
+        )
+        .unwrap();
+
+        // = When a recipient has been added
+        // = * It shows the registration as pending
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::PendingRecipients {}).unwrap())
+                .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::PendingRecipients { pending_recipients } => {
+                assert_eq!(
+                    pending_recipients,
+                    vec![PendingRecipientInfo {
+                        contract: mock_yield_optimizer_smart_contract(),
+                        delivery_mode: DeliveryMode::default(),
+                        replaces: None,
+                        weight: Uint128(1),
+                    }]
+                );
+            }
+            _ => panic!("unexpected error"),
+        }
+        // = * It does not give the pending recipient any Buttcoin
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 110),
+            Uint128(1_000)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 110),
+            Uint128(0)
+        );
+        // = * It does not let the recipient be added again
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+                ButtcoinDistributorHandleMsg::AddRecipient {
+                    delivery_mode: None,
+                    recipient: mock_yield_optimizer_smart_contract(),
+                    weight: Uint128(1),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::AlreadyPending)
+        );
+
+        // = When accepted by an address without a pending registration
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("user", 110),
+                ButtcoinDistributorHandleMsg::AcceptRecipient {
+                    contract_hash: mock_yield_optimizer_smart_contract().contract_hash,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::NoPendingRegistration)
+        );
+
+        // = When accepted with the wrong contract hash
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(mock_yield_optimizer_smart_contract().address.as_str(), 110),
+                ButtcoinDistributorHandleMsg::AcceptRecipient {
+                    contract_hash: "wrong-contract-hash".to_string(),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::ContractHashMismatch)
+        );
+
+        // = When accepted by the registered contract with its contract hash
+        // = * It registers the recipient from then on
+        let res = handle(
+            &mut deps,
+            mock_env_at_block(mock_yield_optimizer_smart_contract().address.as_str(), 110),
+            ButtcoinDistributorHandleMsg::AcceptRecipient {
+                contract_hash: mock_yield_optimizer_smart_contract().contract_hash,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.log,
+            vec![log(
+                "accepted_recipient",
+                mock_yield_optimizer_smart_contract().address
+            )]
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 120),
+            Uint128(500)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 120),
+            Uint128(1_500)
+        );
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::PendingRecipients {}).unwrap())
+                .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::PendingRecipients { pending_recipients } => {
+                assert_eq!(pending_recipients, vec![]);
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_handle_set_recipient_weight() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 3, 100);
+        let handle_msg = ButtcoinDistributorHandleMsg::SetRecipientWeight {
+            address: mock_lp_farm_smart_contract().address,
+            weight: Uint128(1),
+        };
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the address is not a recipient
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+                ButtcoinDistributorHandleMsg::SetRecipientWeight {
+                    address: HumanAddr::from("stranger"),
+                    weight: Uint128(1),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::NotRecipient {
+                address: HumanAddr::from("stranger")
+            })
+        );
+
+        // = When called by the admin
+        // = * It keeps what was earned with the old weight and uses the new weight from then on
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+            handle_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 120),
+            Uint128(750)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 120),
+            Uint128(1_250)
+        );
+
+        // = When gauge voting is enabled
+        // = * It raises an error
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 120),
+            ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 10 },
+        )
+        .unwrap();
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 120),
+                handle_msg
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::WeightsSetByGauge)
+        );
+    }
+
+    #[test]
+    fn test_handle_add_recipient_with_delivery_mode() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        let contract_helper = |name: &str| SecretContract {
+            address: HumanAddr::from(name),
+            contract_hash: format!("{:0>64}", hex::encode(name)),
+        };
+        let delivery_modes = vec![
+            ("vault", DeliveryMode::DepositButtcoin {}),
+            (
+                "lpfarm",
+                DeliveryMode::Send {
+                    msg: Some(Binary::from(
+                        b"{\"stake\":{\"amount\":\"{{amount}}\"}}".to_vec(),
+                    )),
+                },
+            ),
+            (
+                "dao",
+                DeliveryMode::SendAndNotify {
+                    msg: Binary::from(b"{\"funded\":{\"amount\":\"{{amount}}\"}}".to_vec()),
+                },
+            ),
+            ("treasury", DeliveryMode::Transfer {}),
+        ];
+        for (name, delivery_mode) in delivery_modes {
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+                ButtcoinDistributorHandleMsg::AddRecipient {
+                    delivery_mode: Some(delivery_mode),
+                    recipient: contract_helper(name),
+                    weight: Uint128(1),
+                },
+            )
+            .unwrap();
+            accept_recipient_helper(&mut deps, contract_helper(name), 100);
+        }
+
+        // = When the recipients are paid out
+        // = * It sends to each recipient the way it was registered with
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 110),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![
+                snip20::send_msg(
+                    contract_helper("vault").address,
+                    Uint128(250),
+                    Some(
+                        to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook: None })
+                            .unwrap()
+                    ),
+                    None,
+                    1,
+                    mock_buttcoin().contract_hash,
+                    mock_buttcoin().address,
+                )
+                .unwrap(),
+                snip20::send_msg(
+                    contract_helper("lpfarm").address,
+                    Uint128(250),
+                    Some(Binary::from(b"{\"stake\":{\"amount\":\"250\"}}".to_vec())),
+                    None,
+                    1,
+                    mock_buttcoin().contract_hash,
+                    mock_buttcoin().address,
+                )
+                .unwrap(),
+                snip20::send_msg(
+                    contract_helper("dao").address,
+                    Uint128(250),
+                    None,
+                    None,
+                    1,
+                    mock_buttcoin().contract_hash,
+                    mock_buttcoin().address,
+                )
+                .unwrap(),
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_helper("dao").address,
+                    callback_code_hash: contract_helper("dao").contract_hash,
+                    msg: Binary::from(b"{\"funded\":{\"amount\":\"250\"}}".to_vec()),
+                    send: vec![],
+                }),
+                snip20::transfer_msg(
+                    contract_helper("treasury").address,
+                    Uint128(250),
+                    None,
+                    1,
+                    mock_buttcoin().contract_hash,
+                    mock_buttcoin().address,
+                )
+                .unwrap(),
+            ]
+        );
+
+        // = When a recipient claims
+        // = * It sends the claim the way the recipient was registered with
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block(contract_helper("treasury").address.as_str(), 120),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::transfer_msg(
+                contract_helper("treasury").address,
+                Uint128(250),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_handle_expire_rewards() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        let yield_optimizer = mock_yield_optimizer_smart_contract();
+        let expire_rewards_msg = ButtcoinDistributorHandleMsg::ExpireRewards {
+            recipients: vec![yield_optimizer.address.clone()],
+        };
+        let expiring_rewards_helper =
+            |deps: &Extern<MockStorage, MockApi, MockQuerier>| match from_binary(
+                &query(
+                    deps,
+                    ButtcoinDistributorQueryMsg::ExpiringRewards {
+                        recipient: mock_yield_optimizer_smart_contract().address,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+            {
+                ButtcoinDistributorQueryAnswer::ExpiringRewards { accrual_buckets } => {
+                    accrual_buckets
+                }
+                _ => panic!("unexpected error"),
+            };
+
+        // = When reward expiry is set by someone other than the admin
+        // = * It raises an error
+        let set_reward_expiry_msg = ButtcoinDistributorHandleMsg::SetRewardExpiry {
+            bucket_length: 10,
+            destination: ExpiryDestination::Redistribute {},
+            expiry_blocks: Some(20),
+        };
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("user", 100),
+                set_reward_expiry_msg.clone()
+            )
+            .unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When reward expiry is set with a bucket length of zero
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+                ButtcoinDistributorHandleMsg::SetRewardExpiry {
+                    bucket_length: 0,
+                    destination: ExpiryDestination::Redistribute {},
+                    expiry_blocks: Some(20),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::MustBePositive {
+                field: "Bucket length"
+            })
+        );
+
+        // = When the treasury to send expired rewards to is not a valid address
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+                ButtcoinDistributorHandleMsg::SetRewardExpiry {
+                    bucket_length: 10,
+                    destination: ExpiryDestination::Treasury {
+                        address: HumanAddr::from("tr")
+                    },
+                    expiry_blocks: Some(20),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::InvalidAddress {
+                address: HumanAddr::from("tr")
+            })
+        );
+
+        // = When reward expiry is set by the admin
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+            set_reward_expiry_msg,
+        )
+        .unwrap();
+        // == When expiring the rewards of a recipient that is not registered
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("keeper", 110),
+                ButtcoinDistributorHandleMsg::ExpireRewards {
+                    recipients: vec![HumanAddr::from("unknown")],
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::NotRecipient {
+                address: HumanAddr::from("unknown"),
+            })
+        );
+        // == When expiring the rewards of a recipient
+        // == * It puts what has accrued up for expiry after the end of the bucket it was earned in
+        handle(
+            &mut deps,
+            mock_env_at_block("keeper", 110),
+            expire_rewards_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            expiring_rewards_helper(&deps),
+            vec![AccrualBucket {
+                amount: Uint128(500),
+                expires_at_block: 130,
+            }]
+        );
+        // == When the recipient claims part of what has accrued
+        // == * It pays out of the oldest bucket first
+        handle(
+            &mut deps,
+            mock_env_at_block(yield_optimizer.address.as_str(), 125),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: Some(Uint128(200)),
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            expiring_rewards_helper(&deps),
+            vec![
+                AccrualBucket {
+                    amount: Uint128(300),
+                    expires_at_block: 130,
+                },
+                AccrualBucket {
+                    amount: Uint128(500),
+                    expires_at_block: 140,
+                },
+                AccrualBucket {
+                    amount: Uint128(250),
+                    expires_at_block: 150,
+                }
+            ]
+        );
+        // == When buckets have expired
+        // == * It forfeits the buckets and spreads them over the recipients by weight
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 140),
+            expire_rewards_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(handle_result.log, vec![log("expired_amount", 800)]);
+        assert_eq!(
+            expiring_rewards_helper(&deps),
+            vec![
+                AccrualBucket {
+                    amount: Uint128(500),
+                    expires_at_block: 150,
+                },
+                AccrualBucket {
+                    amount: Uint128(500),
+                    expires_at_block: 160,
+                }
+            ]
+        );
+        assert_eq!(
+            pending_helper(&deps, yield_optimizer.clone(), 141),
+            Uint128(1_450)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 141),
+            Uint128(2_450)
+        );
+
+        // = When expired rewards go to a treasury
+        // = * It transfers what has expired to the treasury
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 140),
+            ButtcoinDistributorHandleMsg::SetRewardExpiry {
+                bucket_length: 10,
+                destination: ExpiryDestination::Treasury {
+                    address: HumanAddr::from("treasury"),
+                },
+                expiry_blocks: Some(20),
+            },
+        )
+        .unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 150),
+            expire_rewards_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::transfer_msg(
+                HumanAddr::from("treasury"),
+                Uint128(500),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+        let res = from_binary(&query(&deps, ButtcoinDistributorQueryMsg::RewardExpiry {}).unwrap())
+            .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::RewardExpiry { total_expired, .. } => {
+                assert_eq!(total_expired, Uint128(1_300));
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_handle_expire_rewards_of_untouched_recipients() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        let set_reward_expiry_helper = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                                        expiry_blocks: u64,
+                                        block: u64| {
+            handle(
+                deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, block),
+                ButtcoinDistributorHandleMsg::SetRewardExpiry {
+                    bucket_length: 10,
+                    destination: ExpiryDestination::Treasury {
+                        address: HumanAddr::from("treasury"),
+                    },
+                    expiry_blocks: Some(expiry_blocks),
+                },
+            )
+            .unwrap();
+        };
+        let expire_rewards_helper = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                                     recipient: SecretContract,
+                                     block: u64| {
+            let handle_result = handle(
+                deps,
+                mock_env_at_block("keeper", block),
+                ButtcoinDistributorHandleMsg::ExpireRewards {
+                    recipients: vec![recipient.address.clone()],
+                },
+            )
+            .unwrap();
+            let recipient = recipients_read(&deps.storage)
+                .load(recipient.address.0.as_bytes())
+                .unwrap();
+            (
+                paid_out_helper(&handle_result.messages),
+                recipient.accrual_buckets,
+            )
+        };
+        set_reward_expiry_helper(&mut deps, 20, 100);
+
+        // = When a recipient has not been touched for longer than the expiry
+        // = * It forfeits what was earned before the expiry
+        // = * It puts the rest up for expiry after the end of the buckets it was earned in
+        let (expired, accrual_buckets) =
+            expire_rewards_helper(&mut deps, mock_yield_optimizer_smart_contract(), 200);
+        assert_eq!(expired, 4_000);
+        assert_eq!(
+            accrual_buckets,
+            vec![
+                AccrualBucket {
+                    amount: Uint128(500),
+                    expires_at_block: 210,
+                },
+                AccrualBucket {
+                    amount: Uint128(500),
+                    expires_at_block: 220,
+                }
+            ]
+        );
+
+        // = When more buckets would be live than are added at once
+        // = * It merges neighbouring buckets, which expire with the last of them
+        set_reward_expiry_helper(&mut deps, 1_000, 200);
+        let (expired, accrual_buckets) =
+            expire_rewards_helper(&mut deps, mock_lp_farm_smart_contract(), 300);
+        assert_eq!(expired, 0);
+        assert_eq!(accrual_buckets.len() as u64, MAX_NEW_ACCRUAL_BUCKETS);
+        assert_eq!(
+            accrual_buckets[0],
+            AccrualBucket {
+                amount: Uint128(1_000),
+                expires_at_block: 1_120,
+            }
+        );
+        assert_eq!(
+            accrual_buckets[9],
+            AccrualBucket {
+                amount: Uint128(1_000),
+                expires_at_block: 1_300,
+            }
+        );
+    }
+
+    #[test]
+    fn test_handle_expire_rewards_accrued_before_expiry() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 300, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 200),
+            ButtcoinDistributorHandleMsg::SetRewardExpiry {
+                bucket_length: 10,
+                destination: ExpiryDestination::Treasury {
+                    address: HumanAddr::from("treasury"),
+                },
+                expiry_blocks: Some(20),
+            },
+        )
+        .unwrap();
+
+        // = When rewards were accrued before expiry was turned on
+        // = * It only forfeits what was earned afterwards
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 1_000),
+            ButtcoinDistributorHandleMsg::ExpireRewards {
+                recipients: vec![mock_yield_optimizer_smart_contract().address],
+            },
+        )
+        .unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 5_000);
+        // = * It never puts what was earned before up for expiry
+        let recipient = recipients_read(&deps.storage)
+            .load(mock_yield_optimizer_smart_contract().address.0.as_bytes())
+            .unwrap();
+        assert_eq!(recipient.accrual_buckets, vec![]);
+        assert_eq!(recipient.unexpiring, Uint128(5_000));
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 1_000),
+            Uint128(5_000)
+        );
+        // = * It pays it out when claimed
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block(
+                mock_yield_optimizer_smart_contract().address.as_str(),
+                1_000,
+            ),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 5_000);
+        let recipient = recipients_read(&deps.storage)
+            .load(mock_yield_optimizer_smart_contract().address.0.as_bytes())
+            .unwrap();
+        assert_eq!(recipient.unexpiring, Uint128(0));
+    }
+
+    #[test]
+    fn test_handle_distribute() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        let send_helper = |recipient: SecretContract, amount: u128, hook: Option<Binary>| {
+            snip20::send_msg(
+                recipient.address,
+                Uint128(amount),
+                Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook }).unwrap()),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()
+        };
+
+        // = When there are no recipients
+        // = * It sends nothing
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 100),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(handle_result.messages, vec![]);
+        let handle_result_data: ButtcoinDistributorHandleAnswer =
+            from_binary(&handle_result.data.unwrap()).unwrap();
+        assert_eq!(
+            to_binary(&handle_result_data).unwrap(),
+            to_binary(&ButtcoinDistributorHandleAnswer::Distribute { status: Success }).unwrap()
+        );
+
+        // = When there are recipients
+        let third_smart_contract = SecretContract {
+            address: HumanAddr::from("thirdcontract"),
+            contract_hash: "e".repeat(64),
+        };
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, third_smart_contract.clone(), 2, 100);
+        let hook = Some(to_binary(&"hook").unwrap());
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetDistribution {
+                hook: hook.clone(),
+                max_recipients_per_call: 2,
+            },
+        )
+        .unwrap();
+
+        // == When there are more recipients than the max per call
+        // == * It pays the recipients from the cursor up to the max with the configured hook
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 110),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![
+                send_helper(mock_yield_optimizer_smart_contract(), 250, hook.clone()),
+                send_helper(mock_lp_farm_smart_contract(), 250, hook.clone()),
+            ]
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 110),
+            Uint128(0)
+        );
+        assert_eq!(
+            pending_helper(&deps, third_smart_contract.clone(), 110),
+            Uint128(500)
+        );
+
+        // == When called again
+        // == * It continues from the cursor and wraps around, skipping recipients with nothing to
+        // == receive
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 110),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![send_helper(third_smart_contract.clone(), 500, hook.clone())]
+        );
+        let distribution = distribution_config_read(&deps.storage).load().unwrap();
+        assert_eq!(distribution.cursor, 1);
+
+        // == When a recipient claimed in between
+        // == * It only sends what was released since the claim
+        handle(
+            &mut deps,
+            mock_env_at_block(mock_lp_farm_smart_contract().address.as_str(), 114),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 120),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![
+                send_helper(mock_lp_farm_smart_contract(), 150, hook.clone()),
+                send_helper(third_smart_contract, 500, hook),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_handle_set_fees() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        let handle_msg = ButtcoinDistributorHandleMsg::SetFees {
+            burn_basis_points: 500,
+            treasury: Some(HumanAddr::from("treasury")),
+            treasury_basis_points: 1_000,
+        };
+        let treasury_helper = |amount: u128| {
+            snip20::transfer_msg(
+                HumanAddr::from("treasury"),
+                Uint128(amount),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()
+        };
+        let burn_helper = |amount: u128| {
+            snip20::burn_msg(
+                Uint128(amount),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()
+        };
+        let send_helper = |recipient: SecretContract, amount: u128| {
+            snip20::send_msg(
+                recipient.address,
+                Uint128(amount),
+                Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook: None }).unwrap()),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()
+        };
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the fees add up to more than 10000 basis points
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::SetFees {
+                    burn_basis_points: 5_000,
+                    treasury: Some(HumanAddr::from("treasury")),
+                    treasury_basis_points: 5_001,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::TooManyBasisPoints { subject: "Fees" })
+        );
+
+        // = When there is a treasury fee without a treasury
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::SetFees {
+                    burn_basis_points: 0,
+                    treasury: None,
+                    treasury_basis_points: 1,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::TreasuryRequired)
+        );
+
+        // = When the treasury is not a valid address
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::SetFees {
+                    burn_basis_points: 0,
+                    treasury: Some(HumanAddr::from("tr")),
+                    treasury_basis_points: 1,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::InvalidAddress {
+                address: HumanAddr::from("tr")
+            })
+        );
+
+        // = When called by the admin
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            handle_msg,
+        )
+        .unwrap();
+
+        // == When a recipient claims
+        // == * It sends the treasury fee and burns its share before sending the rest
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block(mock_yield_optimizer_smart_contract().address.as_str(), 110),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![
+                treasury_helper(50),
+                burn_helper(25),
+                send_helper(mock_yield_optimizer_smart_contract(), 425),
+            ]
+        );
+        let handle_result_data: ButtcoinDistributorHandleAnswer =
+            from_binary(&handle_result.data.unwrap()).unwrap();
+        assert_eq!(
+            to_binary(&handle_result_data).unwrap(),
+            to_binary(&ButtcoinDistributorHandleAnswer::ClaimButtcoin {
+                burned_amount: Uint128(25),
+                sent_amount: Uint128(425),
+                status: Success,
+                treasury_amount: Uint128(50),
+            })
+            .unwrap()
+        );
+
+        // == When Distribute is called
+        // == * It takes the same fees out of every payout
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 110),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![
+                treasury_helper(50),
+                burn_helper(25),
+                send_helper(mock_lp_farm_smart_contract(), 425),
+            ]
+        );
+
+        // == When querying the fee stats
+        // == * It returns the settings and the totals so far
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::FeeStats {}).unwrap()).unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::FeeStats {
+                burn_basis_points,
+                total_burned,
+                total_treasury,
+                treasury,
+                treasury_basis_points,
+            } => {
+                assert_eq!(burn_basis_points, 500);
+                assert_eq!(total_burned, Uint128(50));
+                assert_eq!(total_treasury, Uint128(100));
+                assert_eq!(treasury, Some(HumanAddr::from("treasury")));
+                assert_eq!(treasury_basis_points, 1_000);
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_handle_set_hook_validation() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        let handle_msg = ButtcoinDistributorHandleMsg::SetHookValidation {
+            max_size: 200,
+            strict: true,
+        };
+        let claim_helper = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>, hook: Binary| {
+            handle(
+                deps,
+                mock_env_at_block(mock_yield_optimizer_smart_contract().address.as_str(), 110),
+                ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                    amount: None,
+                    hook: Some(hook),
+                    recipient: None,
+                },
+            )
+        };
+        let valid_hook = to_binary(
+            &YieldOptimizerDepositButtcoinHookMsg::ContinueDepositAfterButtcoinClaimed {
+                depositer: HumanAddr::from("depositer"),
+                incentivized_token_amount: Uint128(1),
+            },
+        )
+        .unwrap();
+        let malformed_hook = Binary::from(b"{\"unknown\":{}}".to_vec());
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When strict mode is turned on with a max size of zero
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::SetHookValidation {
+                    max_size: 0,
+                    strict: true,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::MustBePositive {
+                field: "Max hook size"
+            })
+        );
+
+        // = When strict mode is off
+        // = * It forwards any hook
+        claim_helper(&mut deps, malformed_hook.clone()).unwrap();
+
+        // = When strict mode is on
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            handle_msg,
+        )
+        .unwrap();
+
+        // == When the hook is not a YieldOptimizer deposit Buttcoin hook
+        // == * It raises an error
+        assert_eq!(
+            claim_helper(&mut deps, malformed_hook).unwrap_err(),
+            StdError::from(ContractError::InvalidHook)
+        );
+
+        // == When the hook is larger than the maximum size
+        // == * It raises an error
+        assert_eq!(
+            claim_helper(&mut deps, Binary::from(vec![b' '; 201])).unwrap_err(),
+            StdError::from(ContractError::HookTooLarge { max_size: 200 })
+        );
+
+        // == When the hook is valid
+        // == * It forwards the hook
+        let handle_result = claim_helper(&mut deps, valid_hook.clone()).unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::send_msg(
+                mock_yield_optimizer_smart_contract().address,
+                Uint128(0),
+                Some(
+                    to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin {
+                        hook: Some(valid_hook)
+                    })
+                    .unwrap()
+                ),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_handle_set_recipient_hook_variants() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetHookValidation {
+                max_size: 200,
+                strict: true,
+            },
+        )
+        .unwrap();
+        let handle_msg = ButtcoinDistributorHandleMsg::SetRecipientHookVariants {
+            address: mock_lp_farm_smart_contract().address,
+            hook_variants: Some(vec!["stake".to_string()]),
+        };
+        let claim_helper = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>, hook: &[u8]| {
+            handle(
+                deps,
+                mock_env_at_block(mock_lp_farm_smart_contract().address.as_str(), 110),
+                ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                    amount: None,
+                    hook: Some(Binary::from(hook.to_vec())),
+                    recipient: None,
+                },
+            )
+        };
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the address is not a recipient
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::SetRecipientHookVariants {
+                    address: HumanAddr::from("stranger"),
+                    hook_variants: None,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::NotRecipient {
+                address: HumanAddr::from("stranger")
+            })
+        );
+
+        // = When called by the admin
+        // = * It only accepts the registered variants for the recipient
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            handle_msg,
+        )
+        .unwrap();
+        let variant_error = StdError::from(ContractError::HookNotAllowed);
+        assert_eq!(
+            claim_helper(&mut deps, b"{\"unstake\":{}}").unwrap_err(),
+            variant_error
+        );
+        assert_eq!(
+            claim_helper(&mut deps, b"{\"stake\":{},\"unstake\":{}}").unwrap_err(),
+            variant_error
+        );
+        assert_eq!(
+            claim_helper(&mut deps, b"not json").unwrap_err(),
+            variant_error
+        );
+        claim_helper(&mut deps, b"{\"stake\":{\"amount\":\"1\"}}").unwrap();
+    }
+
+    #[test]
+    fn test_handle_set_keeper() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        let handle_msg = ButtcoinDistributorHandleMsg::SetKeeper {
+            bounty: KeeperBounty::BasisPoints {
+                basis_points: 1_000,
+                cap: Uint128(150),
+            },
+            min_interval: 10,
+        };
+        let keeper_helper = |deps: &Extern<MockStorage, MockApi, MockQuerier>| {
+            from_binary(&query(deps, ButtcoinDistributorQueryMsg::Keeper {}).unwrap()).unwrap()
+        };
+        let bounty_helper = |amount: u128| {
+            snip20::transfer_msg(
+                HumanAddr::from("keeper"),
+                Uint128(amount),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()
+        };
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the basis points are more than 10000
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::SetKeeper {
+                    bounty: KeeperBounty::BasisPoints {
+                        basis_points: 10_001,
+                        cap: Uint128(150),
+                    },
+                    min_interval: 10,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::BasisPointsOutOfRange)
+        );
+
+        // = When called by the admin
+        // = * It pays the keeper a share of what Distribute sends, up to the cap
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            handle_msg,
+        )
+        .unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 110),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(handle_result.messages[1], bounty_helper(100));
+
+        // == When Distribute is called again before the minimum interval has passed
+        // == * It does not pay the keeper
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 119),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(handle_result.messages.len(), 1);
+
+        // == When Distribute is called after the minimum interval
+        // == * It pays the keeper at most the cap
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 139),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(handle_result.messages[1], bounty_helper(150));
+
+        // == When Distribute has nothing to send
+        // == * It does not pay the keeper
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 139),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(handle_result.messages, vec![]);
+
+        // == When the bounty is a fixed amount
+        // == * It pays the fixed amount and keeps the totals
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetKeeper {
+                bounty: KeeperBounty::Fixed { amount: Uint128(5) },
+                min_interval: 0,
+            },
+        )
+        .unwrap();
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 140),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(handle_result.messages[1], bounty_helper(5));
+        match keeper_helper(&deps) {
+            ButtcoinDistributorQueryAnswer::Keeper {
+                bounty,
+                last_paid_block,
+                min_interval,
+                paid_cranks,
+                total_paid,
+            } => {
+                assert_eq!(bounty, Some(KeeperBounty::Fixed { amount: Uint128(5) }));
+                assert_eq!(last_paid_block, Some(140));
+                assert_eq!(min_interval, 0);
+                assert_eq!(paid_cranks, 3);
+                assert_eq!(total_paid, Uint128(255));
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_handle_set_distribution() {
+        let (_init_result, mut deps) = init_helper();
+        let handle_msg = ButtcoinDistributorHandleMsg::SetDistribution {
+            hook: None,
+            max_recipients_per_call: 5,
+        };
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When max recipients per call is zero
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::SetDistribution {
+                    hook: None,
+                    max_recipients_per_call: 0,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::MustBePositive {
+                field: "Max recipients per call"
+            })
+        );
+
+        // = When called by the admin
+        // = * It sets the distribution settings
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            handle_msg,
+        )
+        .unwrap();
+        let distribution = distribution_config_read(&deps.storage).load().unwrap();
+        assert_eq!(distribution.max_recipients_per_call, 5);
+        assert_eq!(distribution.hook, None);
+    }
+
+    #[test]
+    fn test_handle_enable_gauge_voting() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        let handle_msg = ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 10 };
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the epoch length is zero
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 0 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::MustBePositive {
+                field: "Epoch length"
+            })
+        );
+
+        // = When called by the admin
+        // = * It enables gauge voting from the current epoch
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 125),
+            handle_msg.clone(),
+        )
+        .unwrap();
+        let gauge = gauge_config_read(&deps.storage).load().unwrap();
+        assert_eq!(gauge.epoch, 2);
+        assert_eq!(gauge.epoch_length, 10);
+
+        // = When gauge voting is already enabled
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                handle_msg
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::GaugeVotingAlreadyEnabled)
+        );
+
+        // = When a recipient accepts afterwards
+        // = * It starts without weight instead of the weight it was added with
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 5, 125);
+        let recipient = recipients_read(&deps.storage)
+            .load(mock_yield_optimizer_smart_contract().address.0.as_bytes())
+            .unwrap();
+        assert_eq!(recipient.weight, Uint128(0));
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.total_weight, Uint128(0));
+    }
+
+    #[test]
+    fn test_handle_receive() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        let lock_msg = ButtcoinDistributorHandleMsg::Receive {
+            sender: HumanAddr::from("voter"),
+            from: HumanAddr::from("voter"),
+            amount: Uint128(300),
+            msg: Some(to_binary(&ButtcoinDistributorReceiveMsg::LockButtcoin {}).unwrap()),
+        };
+
+        // = When not sent by the Buttcoin contract
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("voter", &[]), lock_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When sent by the Buttcoin contract
+        // == When there is no message
+        // == * It accepts the Buttcoin as funding
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block(mock_buttcoin().address.as_str(), 100),
+            ButtcoinDistributorHandleMsg::Receive {
+                sender: HumanAddr::from("funder"),
+                from: HumanAddr::from("funder"),
+                amount: Uint128(1_000),
+                msg: None,
+            },
+        )
+        .unwrap();
+        let handle_result_data: ButtcoinDistributorHandleAnswer =
+            from_binary(&handle_result.data.unwrap()).unwrap();
+        assert_eq!(
+            to_binary(&handle_result_data).unwrap(),
+            to_binary(&ButtcoinDistributorHandleAnswer::Receive { status: Success }).unwrap()
+        );
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.total_funded, Uint128(1_000));
+
+        // == When locking Buttcoin while gauge voting is not enabled
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(mock_buttcoin().address.as_str(), &[]),
+                lock_msg.clone()
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::GaugeVotingNotEnabled)
+        );
+
+        // == When locking Buttcoin while gauge voting is enabled
+        // == * It adds the Buttcoin to the voter's locked amount
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+            ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 10 },
+        )
+        .unwrap();
+        lock_buttcoin_helper(&mut deps, "voter", 300, 100);
+        lock_buttcoin_helper(&mut deps, "voter", 200, 115);
+        let voter = voters_read(&deps.storage).load(b"voter").unwrap();
+        assert_eq!(voter.locked, Uint128(500));
+        assert_eq!(voter.locked_in_epoch, 1);
+        let gauge = gauge_config_read(&deps.storage).load().unwrap();
+        assert_eq!(gauge.total_locked, Uint128(500));
+        // == * It releases no more than the funded Buttcoin, so that none of it is paid out
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.total_emitted, Uint128(1_000));
+        assert_eq!(emission_headroom(&state), 0);
+    }
+
+    #[test]
+    fn test_handle_vote() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        fund_helper(&mut deps, 4_000, 100);
+        let vote_msg = ButtcoinDistributorHandleMsg::Vote {
+            allocations: vec![VoteAllocation {
+                basis_points: 10_000,
+                recipient: mock_yield_optimizer_smart_contract().address,
+            }],
+        };
+
+        // = When gauge voting is not enabled
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env_at_block("alice", 101), vote_msg.clone()).unwrap_err(),
+            StdError::from(ContractError::GaugeVotingNotEnabled)
+        );
+
+        // = When gauge voting is enabled
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+            ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 10 },
+        )
+        .unwrap();
+
+        // == When voting for a recipient that is not registered
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("alice", 101),
+                ButtcoinDistributorHandleMsg::Vote {
+                    allocations: vec![VoteAllocation {
+                        basis_points: 10_000,
+                        recipient: HumanAddr::from("unknown"),
+                    }],
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::NotRecipient {
+                address: HumanAddr::from("unknown"),
+            })
+        );
+
+        // == When voting for the same recipient twice
+        // == * It raises an error
+        let allocation = VoteAllocation {
+            basis_points: 1_000,
+            recipient: mock_lp_farm_smart_contract().address,
+        };
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("alice", 101),
+                ButtcoinDistributorHandleMsg::Vote {
+                    allocations: vec![allocation.clone(), allocation],
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::DuplicateVote)
+        );
+
+        // == When allocating more than 10000 basis points
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("alice", 101),
+                ButtcoinDistributorHandleMsg::Vote {
+                    allocations: vec![
+                        VoteAllocation {
+                            basis_points: 6_000,
+                            recipient: mock_yield_optimizer_smart_contract().address,
+                        },
+                        VoteAllocation {
+                            basis_points: 5_000,
+                            recipient: mock_lp_farm_smart_contract().address,
+                        },
+                    ],
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::TooManyBasisPoints {
+                subject: "Allocations"
+            })
+        );
+
+        // == When the votes are valid
+        // == * It uses the tally as weights from the next epoch boundary
+        lock_buttcoin_helper(&mut deps, "alice", 300, 101);
+        handle(&mut deps, mock_env_at_block("alice", 101), vote_msg).unwrap();
+        lock_buttcoin_helper(&mut deps, "bob", 100, 102);
+        handle(
+            &mut deps,
+            mock_env_at_block("bob", 102),
+            ButtcoinDistributorHandleMsg::Vote {
+                allocations: vec![VoteAllocation {
+                    basis_points: 10_000,
+                    recipient: mock_lp_farm_smart_contract().address,
+                }],
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 120),
+            Uint128(1_250)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 120),
+            Uint128(750)
+        );
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block(mock_yield_optimizer_smart_contract().address.as_str(), 120),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::send_msg(
+                mock_yield_optimizer_smart_contract().address,
+                Uint128(1_250),
+                Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook: None }).unwrap()),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 120),
+            Uint128(750)
+        );
+
+        // == When far more Buttcoin is locked than has been released
+        // == * It uses the shares of the tally as weights
+        lock_buttcoin_helper(&mut deps, "carol", 1_000_000_000_000_000_000, 121);
+        handle(
+            &mut deps,
+            mock_env_at_block("carol", 121),
+            ButtcoinDistributorHandleMsg::Vote {
+                allocations: vec![VoteAllocation {
+                    basis_points: 10_000,
+                    recipient: mock_lp_farm_smart_contract().address,
+                }],
+            },
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env_at_block("carol", 135),
+            ButtcoinDistributorHandleMsg::Vote {
+                allocations: vec![VoteAllocation {
+                    basis_points: 10_000,
+                    recipient: mock_lp_farm_smart_contract().address,
+                }],
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            config_read(&deps.storage).load().unwrap().total_weight,
+            Uint128(MAX_TOTAL_WEIGHT - 1)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 140),
+            Uint128(750)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 140),
+            Uint128(2_000)
+        );
+    }
+
+    #[test]
+    fn test_handle_unlock_buttcoin() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+            ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 10 },
+        )
+        .unwrap();
+        lock_buttcoin_helper(&mut deps, "alice", 300, 101);
+        handle(
+            &mut deps,
+            mock_env_at_block("alice", 101),
+            ButtcoinDistributorHandleMsg::Vote {
+                allocations: vec![VoteAllocation {
+                    basis_points: 10_000,
+                    recipient: mock_yield_optimizer_smart_contract().address,
+                }],
+            },
+        )
+        .unwrap();
+
+        // = When unlocking in the epoch the Buttcoin was locked in
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("alice", 109),
+                ButtcoinDistributorHandleMsg::UnlockButtcoin {
+                    amount: Uint128(100)
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::UnlockTooEarly)
+        );
+
+        // = When unlocking more than what is locked
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("alice", 110),
+                ButtcoinDistributorHandleMsg::UnlockButtcoin {
+                    amount: Uint128(301)
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::InsufficientFunds { balance: "locked" })
+        );
+
+        // = When unlocking Buttcoin whose votes count for the current epoch
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("alice", 110),
+                ButtcoinDistributorHandleMsg::UnlockButtcoin {
+                    amount: Uint128(100)
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::UnlockTooEarly)
+        );
+
+        // = When unlocking in the epoch the votes were withdrawn in
+        // = * It raises an error
+        handle(
+            &mut deps,
+            mock_env_at_block("alice", 115),
+            ButtcoinDistributorHandleMsg::Vote {
+                allocations: vec![],
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("alice", 119),
+                ButtcoinDistributorHandleMsg::UnlockButtcoin {
+                    amount: Uint128(100)
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::UnlockTooEarly)
+        );
+
+        // = When unlocking after the epoch the votes were withdrawn in
+        // = * It sends the Buttcoin back
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("alice", 120),
+            ButtcoinDistributorHandleMsg::UnlockButtcoin {
+                amount: Uint128(100),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::transfer_msg(
+                HumanAddr::from("alice"),
+                Uint128(100),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+        let gauge = gauge_config_read(&deps.storage).load().unwrap();
+        assert_eq!(gauge.total_locked, Uint128(200));
+        assert_eq!(gauge.tally, vec![]);
+    }
+
+    #[test]
+    fn test_handle_change_admin() {
+        let (_init_result, mut deps) = init_helper();
+        let handle_msg = ButtcoinDistributorHandleMsg::ChangeAdmin {
+            admin: HumanAddr::from("new_admin"),
+        };
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the new admin is not a valid address
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                ButtcoinDistributorHandleMsg::ChangeAdmin {
+                    admin: HumanAddr::from("ad"),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::InvalidAddress {
+                address: HumanAddr::from("ad")
+            })
+        );
+
+        // = When called by the admin
+        // = * It changes the admin
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            handle_msg,
+        )
+        .unwrap();
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.admin, HumanAddr::from("new_admin"));
+    }
+
+    #[test]
+    fn test_handle_renounce_admin() {
+        let (_init_result, mut deps) = init_helper();
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
+                receivable_smart_contract: mock_yield_optimizer_smart_contract(),
+            },
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env(mock_yield_optimizer_smart_contract().address, &[]),
+            ButtcoinDistributorHandleMsg::AcceptRecipient {
+                contract_hash: mock_yield_optimizer_smart_contract().contract_hash,
+            },
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::AddRecipient {
+                delivery_mode: None,
+                recipient: mock_lp_farm_smart_contract(),
+                weight: Uint128(1),
+            },
+        )
+        .unwrap();
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env("user", &[]),
+                ButtcoinDistributorHandleMsg::RenounceAdmin {}
+            )
+            .unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When called by the admin
+        // = * It freezes the configuration
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::RenounceAdmin {},
+        )
+        .unwrap();
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Config {}).unwrap()).unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Config { frozen, .. } => {
+                assert!(frozen);
+            }
+            _ => panic!("unexpected error"),
+        }
+
+        // == When a privileged handle is called afterwards
+        // == * It raises an error
+        let frozen_error = StdError::from(ContractError::Frozen);
+        let privileged_msgs = vec![
+            ButtcoinDistributorHandleMsg::ChangeAdmin {
+                admin: HumanAddr::from("new_admin"),
+            },
+            ButtcoinDistributorHandleMsg::RenounceAdmin {},
+            ButtcoinDistributorHandleMsg::SetMultisig {
+                signers: vec![HumanAddr::from("alice")],
+                threshold: 1,
+                proposal_expiry: 10,
+            },
+            ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
+                receivable_smart_contract: mock_lp_farm_smart_contract(),
+            },
+            ButtcoinDistributorHandleMsg::SetSchedule {
+                end_block: 130,
+                max_total_emission: None,
+                release_per_block: Uint128(10),
+                release_per_block_denominator: None,
+            },
+        ];
+        for handle_msg in privileged_msgs {
+            assert_eq!(
+                handle(
+                    &mut deps,
+                    mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                    handle_msg
+                )
+                .unwrap_err(),
+                frozen_error
+            );
+        }
+
+        // == When a registration made before the freeze is accepted afterwards
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(mock_lp_farm_smart_contract().address, &[]),
+                ButtcoinDistributorHandleMsg::AcceptRecipient {
+                    contract_hash: mock_lp_farm_smart_contract().contract_hash,
+                }
+            )
+            .unwrap_err(),
+            frozen_error
+        );
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.total_weight, Uint128(1));
+
+        // == When the receivable smart contract claims
+        // == * It still receives its Buttcoin
+        let handle_result = handle(
+            &mut deps,
+            mock_env(mock_yield_optimizer_smart_contract().address, &[]),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::send_msg(
+                mock_yield_optimizer_smart_contract().address,
+                Uint128(34),
+                Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook: None }).unwrap()),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_handle_renounce_admin_with_multisig() {
+        let (_init_result, mut deps) = init_helper();
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetMultisig {
+                signers: vec![HumanAddr::from("alice"), HumanAddr::from("bob")],
+                threshold: 2,
+                proposal_expiry: 10,
+            },
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env_at_block("alice", 100),
+            ButtcoinDistributorHandleMsg::SetSchedule {
+                end_block: 130,
+                max_total_emission: None,
+                release_per_block: Uint128(10),
+                release_per_block_denominator: None,
+            },
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env_at_block("alice", 100),
+            ButtcoinDistributorHandleMsg::RenounceAdmin {},
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env_at_block("bob", 101),
+            ButtcoinDistributorHandleMsg::ApproveProposal { id: 2 },
+        )
+        .unwrap();
+
+        // = When a proposal created before the freeze reaches the threshold
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("bob", 101),
+                ButtcoinDistributorHandleMsg::ApproveProposal { id: 1 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::Frozen)
+        );
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.end_block, 123);
+    }
+
+    #[test]
+    fn test_handle_set_schedule() {
+        let (_init_result, mut deps) = init_helper();
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        let handle_msg = ButtcoinDistributorHandleMsg::SetSchedule {
+            end_block: 130,
+            max_total_emission: Some(Uint128(104)),
+            release_per_block: Uint128(10),
+            release_per_block_denominator: None,
+        };
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When called by the admin
+        // == When the end block is not after the starting block
+        // == * It raises an error
+        for end_block in [121, 122].iter().cloned() {
+            let invalid_handle_msg = ButtcoinDistributorHandleMsg::SetSchedule {
+                end_block,
+                max_total_emission: None,
+                release_per_block: Uint128(10),
+                release_per_block_denominator: None,
+            };
+            assert_eq!(
+                handle(
+                    &mut deps,
+                    mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                    invalid_handle_msg
+                )
+                .unwrap_err(),
+                StdError::from(ContractError::InvalidSchedule {
+                    reason: "End block must be greater than the starting block.",
+                })
+            );
+        }
+
+        // == When nothing is released per block
+        // == * It raises an error like init
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 123),
+                ButtcoinDistributorHandleMsg::SetSchedule {
+                    end_block: 130,
+                    max_total_emission: None,
+                    release_per_block: Uint128(0),
+                    release_per_block_denominator: None,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::MustBePositive {
+                field: "Release per block",
+            })
+        );
+
+        // == When the max total emission is less than what has already been emitted
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 123),
+                ButtcoinDistributorHandleMsg::SetSchedule {
+                    end_block: 130,
+                    max_total_emission: Some(Uint128(33)),
+                    release_per_block: Uint128(10),
+                    release_per_block_denominator: None,
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::InvalidSchedule {
+                reason: "Max total emission can not be less than what has already been emitted.",
+            })
+        );
+
+        // == When the schedule is valid
+        // == * It keeps what has been released so far and applies the new schedule from then on
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 123),
+            handle_msg,
+        )
+        .unwrap();
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.unallocated_rewards, Uint128(34));
+        assert_eq!(state.end_block, 130);
+        assert_eq!(state.last_update_block, 123);
+        assert_eq!(state.max_total_emission, Uint128(104));
+        assert_eq!(state.release_per_block, Uint128(10));
+        let res = from_binary(
+            &query(
+                &deps,
+                ButtcoinDistributorQueryMsg::Pending {
+                    block: 135,
+                    recipient: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::Pending { amount } => {
+                assert_eq!(amount, Uint128(104));
+            }
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_handle_set_multisig() {
+        let (_init_result, mut deps) = init_helper();
+        let signers = vec![
+            HumanAddr::from("alice"),
+            HumanAddr::from("bob"),
+            HumanAddr::from("carol"),
+        ];
+
+        // = When the threshold is greater than the number of signers
+        // = * It raises an error
+        let handle_msg = ButtcoinDistributorHandleMsg::SetMultisig {
+            signers: signers.clone(),
+            threshold: 4,
+            proposal_expiry: 10,
+        };
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                handle_msg
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::InvalidThreshold)
+        );
+
+        // = When a signer is listed twice
+        // = * It raises an error
+        let handle_msg = ButtcoinDistributorHandleMsg::SetMultisig {
+            signers: vec![HumanAddr::from("alice"), HumanAddr::from("alice")],
+            threshold: 1,
+            proposal_expiry: 10,
+        };
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                handle_msg
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::DuplicateSigner)
+        );
+
+        // = When a signer is not a valid address
+        // = * It raises an error
+        let handle_msg = ButtcoinDistributorHandleMsg::SetMultisig {
+            signers: vec![HumanAddr::from("alice"), HumanAddr::from("bo")],
+            threshold: 1,
+            proposal_expiry: 10,
+        };
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                handle_msg
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::InvalidAddress {
+                address: HumanAddr::from("bo")
+            })
+        );
+
+        // = When proposals would expire straight away
+        // = * It raises an error
+        let handle_msg = ButtcoinDistributorHandleMsg::SetMultisig {
+            signers: signers.clone(),
+            threshold: 2,
+            proposal_expiry: 0,
+        };
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                handle_msg
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::MustBePositive {
+                field: "Proposal expiry"
+            })
+        );
+
+        // = When the multisig is valid
+        // = * It stops the admin from acting alone
+        let handle_msg = ButtcoinDistributorHandleMsg::SetMultisig {
+            signers: signers.clone(),
+            threshold: 2,
+            proposal_expiry: 10,
+        };
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            handle_msg,
+        )
+        .unwrap();
+        let set_schedule_msg = ButtcoinDistributorHandleMsg::SetSchedule {
+            end_block: 130,
+            max_total_emission: None,
+            release_per_block: Uint128(10),
+            release_per_block_denominator: None,
+        };
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+                set_schedule_msg.clone()
+            )
+            .unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // == When a signer submits a privileged handle
+        // == * It creates a proposal instead of executing it
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("alice", 100),
+            set_schedule_msg.clone(),
+        )
+        .unwrap();
+        let handle_result_data: ButtcoinDistributorHandleAnswer =
+            from_binary(&handle_result.data.unwrap()).unwrap();
+        assert_eq!(
+            to_binary(&handle_result_data).unwrap(),
+            to_binary(&ButtcoinDistributorHandleAnswer::ProposalCreated {
+                id: 1,
+                status: Success
+            })
+            .unwrap()
+        );
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.end_block, 123);
+
+        // === When the proposal is approved by someone who is not a signer
+        // === * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 101),
+                ButtcoinDistributorHandleMsg::ApproveProposal { id: 1 }
+            )
+            .unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // === When the proposal is approved twice by the same signer
+        // === * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("alice", 101),
+                ButtcoinDistributorHandleMsg::ApproveProposal { id: 1 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::ProposalAlreadyApproved)
+        );
+
+        // === When the proposal reaches the threshold
+        // === * It executes the proposal
+        handle(
+            &mut deps,
+            mock_env_at_block("bob", 110),
+            ButtcoinDistributorHandleMsg::ApproveProposal { id: 1 },
+        )
+        .unwrap();
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.end_block, 130);
+        assert_eq!(state.release_per_block, Uint128(10));
+
+        // === When an executed proposal is approved
+        // === * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("carol", 110),
+                ButtcoinDistributorHandleMsg::ApproveProposal { id: 1 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::ProposalExecuted)
+        );
+
+        // === When a proposal that does not exist is approved
+        // === * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("carol", 110),
+                ButtcoinDistributorHandleMsg::ApproveProposal { id: 99 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::ProposalNotFound { id: 99 })
+        );
+
+        // === When the proposal is approved after it has expired
+        // === * It raises an error
+        handle(&mut deps, mock_env_at_block("alice", 200), set_schedule_msg).unwrap();
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("bob", 211),
+                ButtcoinDistributorHandleMsg::ApproveProposal { id: 2 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::ProposalExpired)
+        );
+
+        // === When the signers are changed
+        // === * It cancels pending proposals
+        let change_admin_msg = ButtcoinDistributorHandleMsg::ChangeAdmin {
+            admin: HumanAddr::from("alice"),
+        };
+        handle(&mut deps, mock_env_at_block("alice", 300), change_admin_msg).unwrap();
+        let handle_msg = ButtcoinDistributorHandleMsg::SetMultisig {
+            signers: vec![HumanAddr::from("alice"), HumanAddr::from("bob")],
+            threshold: 1,
+            proposal_expiry: 10,
+        };
+        handle(&mut deps, mock_env_at_block("bob", 300), handle_msg.clone()).unwrap();
+        handle(
+            &mut deps,
+            mock_env_at_block("carol", 300),
+            ButtcoinDistributorHandleMsg::ApproveProposal { id: 4 },
+        )
+        .unwrap();
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("bob", 301),
+                ButtcoinDistributorHandleMsg::ApproveProposal { id: 3 }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::ProposalCancelled)
+        );
+
+        // === When the threshold is one
+        // === * It executes the proposal straight away
+        let handle_msg = ButtcoinDistributorHandleMsg::SetMultisig {
+            signers: vec![],
+            threshold: 0,
+            proposal_expiry: 0,
+        };
+        handle(&mut deps, mock_env_at_block("bob", 302), handle_msg).unwrap();
+        let multisig = multisig_config_read(&deps.storage).load().unwrap();
+        assert!(!multisig.is_enabled());
+        assert_eq!(multisig.proposal_count, 5);
+
+        // = When the multisig is disabled
+        // = * It lets the admin act alone again
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::ChangeAdmin {
+                admin: HumanAddr::from("new_admin"),
+            },
+        )
+        .unwrap();
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!(state.admin, HumanAddr::from("new_admin"));
+    }
+
+    #[test]
+    fn test_handle_replace_recipient() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+            ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
+                receivable_smart_contract: mock_yield_optimizer_smart_contract(),
+            },
+        )
+        .unwrap();
+        accept_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 100);
+        add_recipient_helper(&mut deps, mock_lp_farm_smart_contract(), 1, 100);
+        let new_yield_optimizer = SecretContract {
+            address: HumanAddr::from("new-yield-optimizer"),
+            contract_hash: "d".repeat(64),
+        };
+        let handle_msg = ButtcoinDistributorHandleMsg::ReplaceRecipient {
+            old: mock_yield_optimizer_smart_contract().address,
+            new: new_yield_optimizer.clone(),
+        };
 
-    // === QUERY ===
+        // = When called by someone other than the admin
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("user", 110),
+                handle_msg.clone()
+            )
+            .unwrap_err(),
+            StdError::unauthorized()
+        );
 
-    #[test]
-    fn test_query_config() {
-        let (_init_result, deps) = init_helper();
-        let res =
-            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Config {}).unwrap()).unwrap();
-        match res {
-            ButtcoinDistributorQueryAnswer::Config {
-                buttcoin,
-                end_block,
-                last_update_block,
-                receivable_smart_contract,
-                release_per_block,
-                starting_block,
-                viewing_key,
-            } => {
-                assert_eq!(buttcoin, mock_buttcoin());
-                assert_eq!(end_block, 123);
-                assert_eq!(last_update_block, 122);
-                assert_eq!(receivable_smart_contract, None);
-                assert_eq!(release_per_block, Uint128(34));
-                assert_eq!(starting_block, 122);
-                assert_eq!(viewing_key, mock_viewing_key());
-            }
-            _ => panic!("unexpected error"),
-        }
-    }
+        // = When the old recipient is not registered
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+                ButtcoinDistributorHandleMsg::ReplaceRecipient {
+                    old: HumanAddr::from("unknown"),
+                    new: new_yield_optimizer.clone(),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::NotRecipient {
+                address: HumanAddr::from("unknown"),
+            })
+        );
 
-    #[test]
-    fn test_query_pending_rewards() {
-        let (_init_result, deps) = init_helper();
+        // = When the new recipient is already registered
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+                ButtcoinDistributorHandleMsg::ReplaceRecipient {
+                    old: mock_yield_optimizer_smart_contract().address,
+                    new: mock_lp_farm_smart_contract(),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::AlreadyRegistered)
+        );
 
-        // = When block specified is smaller or less than the last update block
-        // = * It returns 0
-        let res = from_binary(
-            &query(&deps, ButtcoinDistributorQueryMsg::Pending { block: 122 }).unwrap(),
+        // = When called by the admin
+        // = * It registers the new recipient as pending
+        let res = handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+            handle_msg.clone(),
         )
         .unwrap();
+        assert_eq!(res.messages, vec![]);
+        assert_eq!(
+            res.log,
+            vec![
+                log(
+                    "old_recipient",
+                    mock_yield_optimizer_smart_contract().address
+                ),
+                log("new_recipient", new_yield_optimizer.address.clone()),
+            ]
+        );
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::PendingRecipients {}).unwrap())
+                .unwrap();
         match res {
-            ButtcoinDistributorQueryAnswer::Pending { amount } => {
-                assert_eq!(amount, Uint128(0));
+            ButtcoinDistributorQueryAnswer::PendingRecipients { pending_recipients } => {
+                assert_eq!(
+                    pending_recipients,
+                    vec![PendingRecipientInfo {
+                        contract: new_yield_optimizer.clone(),
+                        delivery_mode: DeliveryMode::default(),
+                        replaces: Some(mock_yield_optimizer_smart_contract().address),
+                        weight: Uint128(1),
+                    }]
+                );
             }
             _ => panic!("unexpected error"),
         }
+        // = * It does not let the old recipient be replaced twice at once
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+                ButtcoinDistributorHandleMsg::ReplaceRecipient {
+                    old: mock_yield_optimizer_smart_contract().address,
+                    new: SecretContract {
+                        address: HumanAddr::from("other-optimizer"),
+                        contract_hash: "d".repeat(64),
+                    },
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::AlreadyPending)
+        );
 
-        // = When block specified is greater than the last update block
-        // == When block specified is less than or equal to the end_block
-        // == * It returns the correct amount
-        let res = from_binary(
-            &query(&deps, ButtcoinDistributorQueryMsg::Pending { block: 123 }).unwrap(),
+        // == When the new recipient has not accepted yet
+        // == * It earns nothing and the old recipient keeps earning
+        assert_eq!(
+            pending_helper(&deps, new_yield_optimizer.clone(), 120),
+            Uint128(0)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 120),
+            Uint128(1_000)
+        );
+
+        // == When the new recipient accepts
+        // == * It pays the old recipient what it has earned
+        let res = handle(
+            &mut deps,
+            mock_env_at_block(new_yield_optimizer.address.as_str(), 120),
+            ButtcoinDistributorHandleMsg::AcceptRecipient {
+                contract_hash: new_yield_optimizer.contract_hash.clone(),
+            },
         )
         .unwrap();
+        assert_eq!(paid_out_helper(&res.messages), 1_000);
+        assert_eq!(
+            res.log,
+            vec![
+                log("accepted_recipient", new_yield_optimizer.address.clone()),
+                log(
+                    "old_recipient",
+                    mock_yield_optimizer_smart_contract().address
+                ),
+                log("paid_amount", 1_000),
+            ]
+        );
+        // == * It gives the weight of the old recipient to the new recipient from then on
+        assert_eq!(
+            pending_helper(&deps, new_yield_optimizer.clone(), 130),
+            Uint128(500)
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_lp_farm_smart_contract(), 130),
+            Uint128(1_500)
+        );
+        // == * It replaces the receivable smart contract
+        let res =
+            from_binary(&query(&deps, ButtcoinDistributorQueryMsg::Config {}).unwrap()).unwrap();
         match res {
-            ButtcoinDistributorQueryAnswer::Pending { amount } => {
-                assert_eq!(amount, Uint128(34));
+            ButtcoinDistributorQueryAnswer::Config {
+                receivable_smart_contract,
+                ..
+            } => {
+                assert_eq!(receivable_smart_contract.unwrap(), new_yield_optimizer);
             }
             _ => panic!("unexpected error"),
         }
-        // == When the block specified is more than the end_block
-        // == * It returns the correct amount
-        let res = from_binary(
-            &query(&deps, ButtcoinDistributorQueryMsg::Pending { block: 123 }).unwrap(),
+        // == * It does not let the old recipient claim or be registered again
+        let res = handle(
+            &mut deps,
+            mock_env_at_block(mock_yield_optimizer_smart_contract().address.as_str(), 120),
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
         )
         .unwrap();
-        match res {
-            ButtcoinDistributorQueryAnswer::Pending { amount } => {
-                assert_eq!(amount, Uint128(34));
-            }
-            _ => panic!("unexpected error"),
-        }
+        assert_eq!(paid_out_helper(&res.messages), 0);
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 120),
+                ButtcoinDistributorHandleMsg::AddRecipient {
+                    delivery_mode: None,
+                    recipient: mock_yield_optimizer_smart_contract(),
+                    weight: Uint128(1),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::RecipientReplaced)
+        );
     }
 
-    // === HANDLE ===
-
     #[test]
     fn test_handle_set_receivable_smart_contract() {
         let (_init_result, mut deps) = init_helper();
-        let env = mock_env("user", &[]);
+        let env = mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]);
+
+        // = When called by someone other than the admin
+        // = * It raises an error
+        let handle_msg = ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
+            receivable_smart_contract: mock_yield_optimizer_smart_contract(),
+        };
+        assert_eq!(
+            handle(&mut deps, mock_env("user", &[]), handle_msg).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the receivable smart contract is not valid
+        // = * It raises an error
+        let handle_msg = ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
+            receivable_smart_contract: SecretContract {
+                address: HumanAddr::from("yo"),
+                contract_hash: mock_yield_optimizer_smart_contract().contract_hash,
+            },
+        };
+        assert_eq!(
+            handle(&mut deps, env.clone(), handle_msg).unwrap_err(),
+            StdError::from(ContractError::InvalidAddress {
+                address: HumanAddr::from("yo")
+            })
+        );
+        let handle_msg = ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
+            receivable_smart_contract: SecretContract {
+                address: mock_yield_optimizer_smart_contract().address,
+                contract_hash: "yieldoptimizerhash".to_string(),
+            },
+        };
+        assert_eq!(
+            handle(&mut deps, env.clone(), handle_msg).unwrap_err(),
+            StdError::from(ContractError::InvalidContractHash {
+                contract_hash: "yieldoptimizerhash".to_string()
+            })
+        );
 
         // = When receivable smart contract is not set
-        // = * It lets user set the receivable smart contract
+        // = * It lets the admin set the receivable smart contract
         let handle_msg = ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
             receivable_smart_contract: mock_buttcoin(),
         };
@@ -312,14 +7052,383 @@ mod tests {
         }
 
         // = When receivable smart contract is set
-        // = * It does not let user change the receivable smart contract
+        // = * It does not let the admin change the receivable smart contract
         let handle_msg = ButtcoinDistributorHandleMsg::SetReceivableSmartContract {
             receivable_smart_contract: mock_buttcoin(),
         };
         assert_eq!(
             handle(&mut deps, env.clone(), handle_msg).unwrap_err(),
-            StdError::generic_err(format!("Receivable smart contract can only be set once!"))
+            StdError::from(ContractError::AlreadySet {
+                setting: "Receivable smart contract"
+            })
+        );
+    }
+
+    #[test]
+    fn test_handle_partial_claims_and_claim_limits() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        let recipient = mock_yield_optimizer_smart_contract().address;
+        let claim_helper = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                            amount: Option<u128>,
+                            block: u64| {
+            handle(
+                deps,
+                mock_env_at_block(recipient.as_str(), block),
+                ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                    amount: amount.map(Uint128),
+                    hook: None,
+                    recipient: None,
+                },
+            )
+        };
+
+        // = When claiming more than has accrued
+        // = * It raises an error
+        assert_eq!(
+            claim_helper(&mut deps, Some(1_001), 110).unwrap_err(),
+            StdError::from(ContractError::InsufficientFunds { balance: "accrued" })
+        );
+
+        // = When claiming part of what has accrued
+        // = * It pays out that amount and leaves the rest accrued
+        let handle_result = claim_helper(&mut deps, Some(400), 110).unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 400);
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 110),
+            Uint128(600)
+        );
+
+        // = When claim limits are set by someone other than the admin
+        // = * It raises an error
+        let set_claim_limits_msg = ButtcoinDistributorHandleMsg::SetClaimLimits {
+            epoch_length: 100,
+            max_per_claim: Some(Uint128(300)),
+            max_per_epoch: Some(Uint128(500)),
+        };
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("user", 110),
+                set_claim_limits_msg.clone()
+            )
+            .unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When a maximum per epoch is set without an epoch length
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+                ButtcoinDistributorHandleMsg::SetClaimLimits {
+                    epoch_length: 0,
+                    max_per_claim: None,
+                    max_per_epoch: Some(Uint128(500)),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::MustBePositive {
+                field: "Epoch length"
+            })
+        );
+
+        // = When claim limits are set by the admin
+        handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 110),
+            set_claim_limits_msg,
+        )
+        .unwrap();
+        let res = from_binary(&query(&deps, ButtcoinDistributorQueryMsg::ClaimLimits {}).unwrap())
+            .unwrap();
+        match res {
+            ButtcoinDistributorQueryAnswer::ClaimLimits {
+                epoch_length,
+                max_per_claim,
+                max_per_epoch,
+            } => {
+                assert_eq!(epoch_length, 100);
+                assert_eq!(max_per_claim, Some(Uint128(300)));
+                assert_eq!(max_per_epoch, Some(Uint128(500)));
+            }
+            _ => panic!("unexpected error"),
+        }
+        // == When claiming more than the maximum per claim
+        // == * It pays out the maximum per claim
+        let handle_result = claim_helper(&mut deps, None, 120).unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 300);
+        // == When claiming more than is left of the maximum per epoch
+        // == * It pays out what is left of the maximum per epoch and carries the rest over
+        let handle_result = claim_helper(&mut deps, None, 130).unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 200);
+        let handle_result = claim_helper(&mut deps, None, 140).unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 0);
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 140),
+            Uint128(3_100)
+        );
+        // == When distributing
+        // == * It applies the same limits
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 150),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 0);
+        // == When the next epoch starts
+        // == * It lets the recipient claim again
+        let handle_result = handle(
+            &mut deps,
+            mock_env_at_block("keeper", 200),
+            ButtcoinDistributorHandleMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 300);
+        let handle_result = claim_helper(&mut deps, None, 210).unwrap();
+        assert_eq!(paid_out_helper(&handle_result.messages), 200);
+    }
+
+    #[test]
+    fn test_handle_claim_operators() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        let recipient = mock_yield_optimizer_smart_contract().address;
+        let add_claim_operator_msg = ButtcoinDistributorHandleMsg::AddClaimOperator {
+            operator: HumanAddr::from("bot"),
+        };
+        let claim_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount: None,
+            hook: None,
+            recipient: Some(recipient.clone()),
+        };
+
+        // = When called by an address that is not a registered recipient
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("user", 100),
+                add_claim_operator_msg.clone()
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::ClaimOperatorsNeedRecipient)
+        );
+
+        // = When claiming for a recipient without being its claim operator
+        // = * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env_at_block("bot", 110), claim_msg.clone()).unwrap_err(),
+            StdError::unauthorized()
+        );
+
+        // = When the claim operator is not a valid address
+        // = * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(recipient.as_str(), 100),
+                ButtcoinDistributorHandleMsg::AddClaimOperator {
+                    operator: HumanAddr::from("op"),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::InvalidAddress {
+                address: HumanAddr::from("op")
+            })
+        );
+
+        // = When the recipient adds a claim operator
+        handle(
+            &mut deps,
+            mock_env_at_block(recipient.as_str(), 100),
+            add_claim_operator_msg.clone(),
+        )
+        .unwrap();
+        // == When the claim operator is added again
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(recipient.as_str(), 100),
+                add_claim_operator_msg
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::AlreadyClaimOperator {
+                operator: HumanAddr::from("bot"),
+            })
+        );
+        // == When the claim operator claims for the recipient
+        // == * It pays the recipient
+        let handle_result =
+            handle(&mut deps, mock_env_at_block("bot", 110), claim_msg.clone()).unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::send_msg(
+                recipient.clone(),
+                Uint128(1_000),
+                Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook: None }).unwrap()),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+        assert_eq!(
+            pending_helper(&deps, mock_yield_optimizer_smart_contract(), 110),
+            Uint128(0)
+        );
+        // == When the claim operator claims with a hook
+        let withdrawal_hook = to_binary(
+            &YieldOptimizerDepositButtcoinHookMsg::ContinueWithdrawalAfterButtcoinClaimed {
+                withdrawer: HumanAddr::from("bot"),
+                shares_amount: Uint128(1),
+            },
+        )
+        .unwrap();
+        let hook_claim_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount: None,
+            hook: Some(withdrawal_hook.clone()),
+            recipient: Some(recipient.clone()),
+        };
+        // === When the recipient has no hook variants registered
+        // === * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("bot", 110),
+                hook_claim_msg.clone()
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::HookNotAllowed)
+        );
+        // === When the hook is not one of the recipient's hook variants
+        // === * It raises an error
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetRecipientHookVariants {
+                address: recipient.clone(),
+                hook_variants: Some(vec!["continue_deposit_after_buttcoin_claimed".to_string()]),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block("bot", 110),
+                hook_claim_msg.clone()
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::HookNotAllowed)
+        );
+        // === When the hook is one of the recipient's hook variants
+        // === * It forwards the hook
+        handle(
+            &mut deps,
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
+            ButtcoinDistributorHandleMsg::SetRecipientHookVariants {
+                address: recipient.clone(),
+                hook_variants: Some(vec![
+                    "continue_withdrawal_after_buttcoin_claimed".to_string()
+                ]),
+            },
+        )
+        .unwrap();
+        let handle_result =
+            handle(&mut deps, mock_env_at_block("bot", 110), hook_claim_msg).unwrap();
+        assert_eq!(
+            handle_result.messages,
+            vec![snip20::send_msg(
+                recipient.clone(),
+                Uint128(0),
+                Some(
+                    to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin {
+                        hook: Some(withdrawal_hook)
+                    })
+                    .unwrap()
+                ),
+                None,
+                1,
+                mock_buttcoin().contract_hash,
+                mock_buttcoin().address,
+            )
+            .unwrap()]
+        );
+
+        // = When the recipient removes the claim operator
+        handle(
+            &mut deps,
+            mock_env_at_block(recipient.as_str(), 110),
+            ButtcoinDistributorHandleMsg::RemoveClaimOperator {
+                operator: HumanAddr::from("bot"),
+            },
+        )
+        .unwrap();
+        // == When the claim operator is removed again
+        // == * It raises an error
+        assert_eq!(
+            handle(
+                &mut deps,
+                mock_env_at_block(recipient.as_str(), 110),
+                ButtcoinDistributorHandleMsg::RemoveClaimOperator {
+                    operator: HumanAddr::from("bot"),
+                }
+            )
+            .unwrap_err(),
+            StdError::from(ContractError::NotClaimOperator {
+                operator: HumanAddr::from("bot"),
+            })
+        );
+        // == When the removed claim operator claims for the recipient
+        // == * It raises an error
+        assert_eq!(
+            handle(&mut deps, mock_env_at_block("bot", 120), claim_msg).unwrap_err(),
+            StdError::unauthorized()
+        );
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let (_init_result, mut deps) = init_helper_with_schedule(100, 1_000, Uint128(100));
+        add_recipient_helper(&mut deps, mock_yield_optimizer_smart_contract(), 1, 100);
+        let add_recipient_msg = ButtcoinDistributorHandleMsg::AddRecipient {
+            delivery_mode: None,
+            recipient: mock_yield_optimizer_smart_contract(),
+            weight: Uint128(1),
+        };
+
+        // = When the contract raises an error
+        // = * It starts the message with the code of the error
+        let error = handle(
+            &mut deps,
+            mock_env_at_block(MOCK_SMART_CONTRACT_INITIALIZER, 100),
+            add_recipient_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            StdError::generic_err("E003: Recipient is already registered.")
         );
+        assert_eq!(
+            error_code(&error),
+            Some(ContractError::AlreadyRegistered.code())
+        );
+
+        // = When the sender is not allowed
+        // = * It raises the unauthorized StdError with its code
+        let error =
+            handle(&mut deps, mock_env_at_block("user", 100), add_recipient_msg).unwrap_err();
+        assert_eq!(error, StdError::unauthorized());
+        assert_eq!(error_code(&error), Some(ContractError::Unauthorized.code()));
+
+        // = When the error did not come from the contract
+        // = * It has no code
+        assert_eq!(error_code(&StdError::generic_err("Recipient")), None);
+        assert_eq!(error_code(&StdError::not_found("Recipient")), None);
     }
 
     #[test]
@@ -337,7 +7446,11 @@ mod tests {
 
         // = When a receivable smart contract is not set
         // = * It returns a send_msg with 0 amount and a hook back to the sender
-        let handle_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin { hook: hook.clone() };
+        let handle_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount: None,
+            hook: hook.clone(),
+            recipient: None,
+        };
         let handle_result = handle(
             &mut deps,
             mock_env(mock_yield_optimizer_smart_contract().address, &[]),
@@ -364,7 +7477,13 @@ mod tests {
             from_binary(&handle_result_unwrapped.data.unwrap()).unwrap();
         assert_eq!(
             to_binary(&handle_result_data).unwrap(),
-            to_binary(&ButtcoinDistributorHandleAnswer::ClaimButtcoin { status: Success }).unwrap()
+            to_binary(&ButtcoinDistributorHandleAnswer::ClaimButtcoin {
+                burned_amount: Uint128(0),
+                sent_amount: Uint128(0),
+                status: Success,
+                treasury_amount: Uint128(0),
+            })
+            .unwrap()
         );
 
         // = When a receivable smart contract is set
@@ -374,13 +7493,25 @@ mod tests {
             };
         handle(
             &mut deps,
-            mock_env(HumanAddr::from("some user"), &[]),
+            mock_env(MOCK_SMART_CONTRACT_INITIALIZER, &[]),
             set_receivable_smart_contract_msg,
         )
         .unwrap();
+        handle(
+            &mut deps,
+            mock_env(mock_yield_optimizer_smart_contract().address, &[]),
+            ButtcoinDistributorHandleMsg::AcceptRecipient {
+                contract_hash: mock_yield_optimizer_smart_contract().contract_hash,
+            },
+        )
+        .unwrap();
         // == When this is called by an address that is not the receivable smart contract
         // == * It returns a send_msg with 0 amount and a hook back to the sender
-        let handle_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin { hook: hook.clone() };
+        let handle_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount: None,
+            hook: hook.clone(),
+            recipient: None,
+        };
         let handle_result = handle(
             &mut deps,
             mock_env(mock_buttcoin().address, &[]),
@@ -407,12 +7538,22 @@ mod tests {
             from_binary(&handle_result_unwrapped.data.unwrap()).unwrap();
         assert_eq!(
             to_binary(&handle_result_data).unwrap(),
-            to_binary(&ButtcoinDistributorHandleAnswer::ClaimButtcoin { status: Success }).unwrap()
+            to_binary(&ButtcoinDistributorHandleAnswer::ClaimButtcoin {
+                burned_amount: Uint128(0),
+                sent_amount: Uint128(0),
+                status: Success,
+                treasury_amount: Uint128(0),
+            })
+            .unwrap()
         );
 
         // == When this is called by the receivable smart contract
         // == * It returns a send_msg with the correct amount and a hook back to the sender
-        let handle_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin { hook: hook.clone() };
+        let handle_msg = ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount: None,
+            hook: hook.clone(),
+            recipient: None,
+        };
         let handle_result = handle(
             &mut deps,
             mock_env(mock_yield_optimizer_smart_contract().address, &[]),
@@ -424,7 +7565,7 @@ mod tests {
             vec![snip20::send_msg(
                 mock_yield_optimizer_smart_contract().address.clone(),
                 Uint128(34),
-                Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook: hook }).unwrap()),
+                Some(to_binary(&YieldOptimizerReceiveMsg::DepositButtcoin { hook }).unwrap()),
                 None,
                 1,
                 mock_buttcoin().contract_hash,
@@ -436,7 +7577,13 @@ mod tests {
             from_binary(&handle_result_unwrapped.data.unwrap()).unwrap();
         assert_eq!(
             to_binary(&handle_result_data).unwrap(),
-            to_binary(&ButtcoinDistributorHandleAnswer::ClaimButtcoin { status: Success }).unwrap()
+            to_binary(&ButtcoinDistributorHandleAnswer::ClaimButtcoin {
+                burned_amount: Uint128(0),
+                sent_amount: Uint128(34),
+                status: Success,
+                treasury_amount: Uint128(0),
+            })
+            .unwrap()
         );
     }
 }
@@ -0,0 +1,165 @@
+use cosmwasm_std::{HumanAddr, StdError};
+use snafu::Snafu;
+
+// Every error the contract raises. They reach callers as a StdError whose message starts with the
+// code of the error, e.g. "E003: Recipient is already registered.", so that other contracts and
+// clients can match on the kind of error instead of its text. Codes are never reused or renumbered.
+#[derive(Debug, PartialEq, Snafu)]
+pub enum ContractError {
+    #[snafu(display("{} is already a claim operator.", operator))]
+    AlreadyClaimOperator { operator: HumanAddr },
+    #[snafu(display("Recipient is already waiting to be accepted."))]
+    AlreadyPending,
+    #[snafu(display("Recipient is already registered."))]
+    AlreadyRegistered,
+    #[snafu(display("{} can only be set once!", setting))]
+    AlreadySet { setting: &'static str },
+    #[snafu(display("Basis points can not be more than 10000."))]
+    BasisPointsOutOfRange,
+    #[snafu(display("Only registered recipients can have claim operators."))]
+    ClaimOperatorsNeedRecipient,
+    #[snafu(display("Contract hash does not match the pending registration."))]
+    ContractHashMismatch,
+    #[snafu(display("Signers must be unique."))]
+    DuplicateSigner,
+    #[snafu(display("Each recipient can only be voted for once."))]
+    DuplicateVote,
+    #[snafu(display("Configuration is frozen and can no longer be changed."))]
+    Frozen,
+    #[snafu(display("Gauge voting is already enabled."))]
+    GaugeVotingAlreadyEnabled,
+    #[snafu(display("Gauge voting is not enabled."))]
+    GaugeVotingNotEnabled,
+    #[snafu(display("Gauge voting was not enabled during this epoch."))]
+    GaugeVotingNotEnabledInEpoch,
+    #[snafu(display("Hook is not one of the variants registered for this recipient."))]
+    HookNotAllowed,
+    #[snafu(display("Hook is larger than the maximum of {} bytes.", max_size))]
+    HookTooLarge { max_size: u64 },
+    #[snafu(display("Amount is greater than the {} Buttcoin.", balance))]
+    InsufficientFunds { balance: &'static str },
+    #[snafu(display("{} is not a valid address.", address))]
+    InvalidAddress { address: HumanAddr },
+    #[snafu(display(
+        "{} is not a valid contract hash, which is 64 hexadecimal characters.",
+        contract_hash
+    ))]
+    InvalidContractHash { contract_hash: String },
+    #[snafu(display("Hook is not a valid YieldOptimizer deposit Buttcoin hook."))]
+    InvalidHook,
+    #[snafu(display("{}", reason))]
+    InvalidSchedule { reason: &'static str },
+    #[snafu(display("Threshold must be between 1 and the number of signers."))]
+    InvalidThreshold,
+    #[snafu(display("Multisig is not enabled."))]
+    MultisigNotEnabled,
+    #[snafu(display("{} must be greater than zero.", field))]
+    MustBePositive { field: &'static str },
+    #[snafu(display("There is no pending registration for this address."))]
+    NoPendingRegistration,
+    #[snafu(display("{} is not a claim operator.", operator))]
+    NotClaimOperator { operator: HumanAddr },
+    #[snafu(display("Message is not a privileged operation."))]
+    NotPrivileged,
+    #[snafu(display("{} is not a registered recipient.", address))]
+    NotRecipient { address: HumanAddr },
+    #[snafu(display("Overflow when calculating {}.", value))]
+    Overflow { value: &'static str },
+    #[snafu(display("Proposal has already been approved by this signer."))]
+    ProposalAlreadyApproved,
+    #[snafu(display("Proposal was cancelled by a change of signers."))]
+    ProposalCancelled,
+    #[snafu(display("Proposal has already been executed."))]
+    ProposalExecuted,
+    #[snafu(display("Proposal has expired."))]
+    ProposalExpired,
+    #[snafu(display("Proposal {} does not exist.", id))]
+    ProposalNotFound { id: u64 },
+    #[snafu(display("Recipient has been replaced and can not be registered again."))]
+    RecipientReplaced,
+    #[snafu(display("{} can not add up to more than 10000 basis points.", subject))]
+    TooManyBasisPoints { subject: &'static str },
+    #[snafu(display("Recipient weights can not add up to more than 1000000."))]
+    TooMuchWeight,
+    #[snafu(display("A treasury is required for a treasury fee."))]
+    TreasuryRequired,
+    #[snafu(display("Unauthorized."))]
+    Unauthorized,
+    #[snafu(display(
+        "Buttcoin can only be unlocked once the epochs it was locked in and voted with have ended."
+    ))]
+    UnlockTooEarly,
+    #[snafu(display("Recipient weights are set by gauge voting."))]
+    WeightsSetByGauge,
+}
+
+impl ContractError {
+    pub fn code(&self) -> u16 {
+        match self {
+            ContractError::AlreadyClaimOperator { .. } => 1,
+            ContractError::AlreadyPending => 2,
+            ContractError::AlreadyRegistered => 3,
+            ContractError::AlreadySet { .. } => 4,
+            ContractError::BasisPointsOutOfRange => 5,
+            ContractError::ClaimOperatorsNeedRecipient => 6,
+            ContractError::ContractHashMismatch => 7,
+            ContractError::DuplicateSigner => 8,
+            ContractError::DuplicateVote => 9,
+            ContractError::Frozen => 10,
+            ContractError::GaugeVotingAlreadyEnabled => 11,
+            ContractError::GaugeVotingNotEnabled => 12,
+            ContractError::GaugeVotingNotEnabledInEpoch => 13,
+            ContractError::HookNotAllowed => 14,
+            ContractError::HookTooLarge { .. } => 15,
+            ContractError::InsufficientFunds { .. } => 16,
+            ContractError::InvalidAddress { .. } => 37,
+            ContractError::InvalidContractHash { .. } => 38,
+            ContractError::InvalidHook => 17,
+            ContractError::InvalidSchedule { .. } => 18,
+            ContractError::InvalidThreshold => 19,
+            ContractError::MultisigNotEnabled => 20,
+            ContractError::MustBePositive { .. } => 21,
+            ContractError::NoPendingRegistration => 22,
+            ContractError::NotClaimOperator { .. } => 23,
+            ContractError::NotPrivileged => 24,
+            ContractError::NotRecipient { .. } => 25,
+            ContractError::Overflow { .. } => 26,
+            ContractError::ProposalAlreadyApproved => 27,
+            ContractError::ProposalCancelled => 28,
+            ContractError::ProposalExecuted => 29,
+            ContractError::ProposalExpired => 30,
+            ContractError::ProposalNotFound { .. } => 39,
+            ContractError::RecipientReplaced => 31,
+            ContractError::TooManyBasisPoints { .. } => 32,
+            ContractError::TooMuchWeight => 40,
+            ContractError::TreasuryRequired => 33,
+            ContractError::Unauthorized => 34,
+            ContractError::UnlockTooEarly => 35,
+            ContractError::WeightsSetByGauge => 36,
+        }
+    }
+}
+
+// Unauthorized stays the StdError that the chain and other contracts already know.
+impl From<ContractError> for StdError {
+    fn from(error: ContractError) -> Self {
+        match error {
+            ContractError::Unauthorized => StdError::unauthorized(),
+            error => StdError::generic_err(format!("E{:03}: {}", error.code(), error)),
+        }
+    }
+}
+
+// The code of an error raised by the contract, if it is one.
+pub fn error_code(error: &StdError) -> Option<u16> {
+    match error {
+        StdError::GenericErr { msg, .. } => {
+            if !msg.starts_with('E') || msg.get(4..6) != Some(": ") {
+                return None;
+            }
+            msg[1..4].parse().ok()
+        }
+        StdError::Unauthorized { .. } => Some(ContractError::Unauthorized.code()),
+        _ => None,
+    }
+}
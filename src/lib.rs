@@ -1,8 +1,15 @@
+#[cfg(feature = "client")]
+pub mod client;
 pub mod contract;
+pub mod error;
+#[cfg(test)]
+mod mock_chain;
 pub mod msg;
 pub mod state;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", not(feature = "client")))]
 mod wasm {
     use cosmwasm_std::{
         do_handle, do_init, do_query, ExternalApi, ExternalQuerier, ExternalStorage,
@@ -0,0 +1,712 @@
+// In-memory chain for end to end tests. Messages returned by the distributor are executed against a
+// mock Buttcoin SNIP-20 and mock YieldOptimizers, including the Receive callbacks of sends, so
+// that tests can check balances instead of the messages themselves.
+// As on a real chain, a failing message reverts everything that the execution it is part of did
+// before it.
+use crate::contract::{handle, init, query};
+use crate::msg::{
+    ButtcoinDistributorHandleMsg, ButtcoinDistributorQueryAnswer, ButtcoinDistributorQueryMsg,
+    InitMsg, YieldOptimizerReceiveMsg,
+};
+use crate::state::{gauge_config_read, SecretContract};
+use crate::testing::{self, Revert, Snip20HandleMsg};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_binary, to_binary, Binary, CosmosMsg, Env, Extern, HandleResponse, HumanAddr, StdError,
+    StdResult, Uint128, WasmMsg,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const ADMIN: &str = "admin";
+pub const BUTTCOIN: &str = "buttcoin";
+pub const DISTRIBUTOR: &str = "buttcoin-distributor";
+pub const VIEWING_KEY: &str = "distributor-viewing-key";
+
+// Sent by the SNIP-20 to contracts that have registered to receive.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Snip20ReceiveMsg {
+    Receive {
+        sender: HumanAddr,
+        from: HumanAddr,
+        amount: Uint128,
+        msg: Option<Binary>,
+    },
+}
+
+#[derive(Clone, Default)]
+pub struct MockSnip20 {
+    pub balances: BTreeMap<HumanAddr, u128>,
+    pub receivers: Vec<HumanAddr>,
+    pub total_supply: u128,
+    pub viewing_keys: BTreeMap<HumanAddr, String>,
+}
+
+impl MockSnip20 {
+    pub fn balance(&self, address: &str) -> u128 {
+        self.balances
+            .get(&HumanAddr::from(address))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn balance_with_viewing_key(&self, address: &str, key: &str) -> StdResult<u128> {
+        match self.viewing_keys.get(&HumanAddr::from(address)) {
+            Some(viewing_key) if viewing_key == key => Ok(self.balance(address)),
+            _ => Err(StdError::unauthorized()),
+        }
+    }
+
+    pub fn mint(&mut self, address: &str, amount: u128) {
+        *self.balances.entry(HumanAddr::from(address)).or_insert(0) += amount;
+        self.total_supply += amount;
+    }
+
+    fn take(&mut self, address: &HumanAddr, amount: u128) -> StdResult<()> {
+        let balance = self.balances.entry(address.clone()).or_insert(0);
+        if *balance < amount {
+            return Err(StdError::generic_err(format!(
+                "insufficient funds: balance={}, required={}",
+                balance, amount
+            )));
+        }
+        *balance -= amount;
+
+        Ok(())
+    }
+
+    // Returns the Receive callback when a send goes to a contract that has registered to receive.
+    fn handle(
+        &mut self,
+        sender: HumanAddr,
+        msg: &Binary,
+    ) -> StdResult<Option<(HumanAddr, Binary)>> {
+        match from_binary(msg)? {
+            Snip20HandleMsg::Burn { amount } => {
+                self.take(&sender, amount.u128())?;
+                self.total_supply -= amount.u128();
+            }
+            Snip20HandleMsg::RegisterReceive { .. } => self.receivers.push(sender),
+            Snip20HandleMsg::Send {
+                recipient,
+                amount,
+                msg,
+            } => {
+                self.take(&sender, amount.u128())?;
+                *self.balances.entry(recipient.clone()).or_insert(0) += amount.u128();
+                if self.receivers.contains(&recipient) {
+                    let callback = to_binary(&Snip20ReceiveMsg::Receive {
+                        sender: sender.clone(),
+                        from: sender,
+                        amount,
+                        msg,
+                    })?;
+                    return Ok(Some((recipient, callback)));
+                }
+            }
+            Snip20HandleMsg::SetViewingKey { key } => {
+                self.viewing_keys.insert(sender, key);
+            }
+            Snip20HandleMsg::Transfer { recipient, amount } => {
+                self.take(&sender, amount.u128())?;
+                *self.balances.entry(recipient).or_insert(0) += amount.u128();
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deposit {
+    pub amount: Uint128,
+    pub from: HumanAddr,
+    pub hook: Option<Binary>,
+}
+
+// Records the Buttcoin sent to it as deposits, and any other message executed on it or sent along
+// with Buttcoin as notifications.
+#[derive(Clone, Default)]
+pub struct MockYieldOptimizer {
+    pub deposits: Vec<Deposit>,
+    pub notifications: Vec<Binary>,
+}
+
+impl MockYieldOptimizer {
+    fn handle(&mut self, sender: HumanAddr, msg: &Binary) -> StdResult<()> {
+        match from_binary(msg) {
+            Ok(Snip20ReceiveMsg::Receive {
+                from, amount, msg, ..
+            }) if sender == HumanAddr::from(BUTTCOIN) => {
+                let hook = match msg {
+                    Some(msg) => match from_binary(&msg) {
+                        Ok(YieldOptimizerReceiveMsg::DepositButtcoin { hook }) => hook,
+                        Err(_) => {
+                            self.notifications.push(msg);
+                            None
+                        }
+                    },
+                    None => None,
+                };
+                self.deposits.push(Deposit { amount, from, hook });
+            }
+            _ => self.notifications.push(msg.clone()),
+        }
+
+        Ok(())
+    }
+}
+
+pub struct MockChain {
+    pub block: u64,
+    pub buttcoin: MockSnip20,
+    pub distributor: Extern<MockStorage, MockApi, MockQuerier>,
+    pub yield_optimizers: BTreeMap<HumanAddr, MockYieldOptimizer>,
+}
+
+impl MockChain {
+    // Starts at starting_block with the distributor instantiated by ADMIN.
+    pub fn new(starting_block: u64, end_block: u64, release_per_block: u128) -> Self {
+        let mut chain = MockChain {
+            block: starting_block,
+            buttcoin: MockSnip20::default(),
+            distributor: mock_dependencies(20, &[]),
+            yield_optimizers: BTreeMap::new(),
+        };
+        let init_msg = InitMsg {
+            buttcoin: chain.buttcoin_contract(),
+            end_block,
+            max_total_emission: None,
+            release_per_block: Uint128(release_per_block),
+            release_per_block_denominator: None,
+            starting_block,
+            viewing_key: VIEWING_KEY.to_string(),
+        };
+        let env = chain.env(ADMIN);
+        let init_response = init(&mut chain.distributor, env, init_msg).unwrap();
+        for message in init_response.messages {
+            chain.route(HumanAddr::from(DISTRIBUTOR), message).unwrap();
+        }
+
+        chain
+    }
+
+    pub fn advance_blocks(&mut self, blocks: u64) {
+        self.block += blocks;
+    }
+
+    pub fn buttcoin_contract(&self) -> SecretContract {
+        SecretContract {
+            address: HumanAddr::from(BUTTCOIN),
+            contract_hash: "b".repeat(64),
+        }
+    }
+
+    // Deploys a yield optimizer that has registered to receive Buttcoin.
+    pub fn add_yield_optimizer(&mut self, address: &str) -> SecretContract {
+        let address = HumanAddr::from(address);
+        self.yield_optimizers
+            .insert(address.clone(), MockYieldOptimizer::default());
+        self.buttcoin.receivers.push(address.clone());
+
+        SecretContract {
+            contract_hash: format!("{:0>64}", hex::encode(address.as_str())),
+            address,
+        }
+    }
+
+    // Executes msg on the distributor and everything that it sends on.
+    pub fn execute(
+        &mut self,
+        sender: &str,
+        msg: ButtcoinDistributorHandleMsg,
+    ) -> StdResult<HandleResponse> {
+        self.execute_or_revert(|chain| chain.execute_distributor(HumanAddr::from(sender), msg))
+    }
+
+    // Sends Buttcoin from sender to the distributor, with msg passed on to its Receive.
+    pub fn send_buttcoin(
+        &mut self,
+        sender: &str,
+        amount: u128,
+        msg: Option<Binary>,
+    ) -> StdResult<()> {
+        let send_msg = to_binary(&secret_toolkit::snip20::HandleMsg::Send {
+            recipient: HumanAddr::from(DISTRIBUTOR),
+            amount: Uint128(amount),
+            msg,
+            padding: None,
+        })?;
+        self.execute_or_revert(|chain| {
+            chain.route_to(HumanAddr::from(sender), HumanAddr::from(BUTTCOIN), send_msg)
+        })
+    }
+
+    pub fn query(&self, msg: ButtcoinDistributorQueryMsg) -> ButtcoinDistributorQueryAnswer {
+        from_binary(&query(&self.distributor, msg).unwrap()).unwrap()
+    }
+
+    fn env(&self, sender: &str) -> Env {
+        let mut env = mock_env(sender, &[]);
+        env.block.height = self.block;
+        env.contract.address = HumanAddr::from(DISTRIBUTOR);
+        env
+    }
+
+    fn execute_or_revert<T>(
+        &mut self,
+        execute: impl FnOnce(&mut Self) -> StdResult<T>,
+    ) -> StdResult<T> {
+        testing::execute_or_revert(self, |chain| {
+            let value = execute(chain)?;
+            chain.check_locked_buttcoin()?;
+            Ok(value)
+        })
+    }
+
+    // Only what the distributor holds besides the Buttcoin locked for gauge voting can be paid out.
+    fn check_locked_buttcoin(&self) -> StdResult<()> {
+        let locked = match gauge_config_read(&self.distributor.storage).may_load()? {
+            Some(gauge) => gauge.total_locked.u128(),
+            None => 0,
+        };
+        let balance = self.buttcoin.balance(DISTRIBUTOR);
+        if balance < locked {
+            return Err(StdError::generic_err(format!(
+                "locked Buttcoin paid out: balance={}, locked={}",
+                balance, locked
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn execute_distributor(
+        &mut self,
+        sender: HumanAddr,
+        msg: ButtcoinDistributorHandleMsg,
+    ) -> StdResult<HandleResponse> {
+        let env = self.env(sender.as_str());
+        let response = handle(&mut self.distributor, env, msg)?;
+        for message in response.messages.clone() {
+            self.route(HumanAddr::from(DISTRIBUTOR), message)?;
+        }
+
+        Ok(response)
+    }
+
+    fn route(&mut self, sender: HumanAddr, message: CosmosMsg) -> StdResult<()> {
+        match message {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => self.route_to(sender, contract_addr, msg),
+            _ => Err(StdError::generic_err("Only Wasm executes are supported.")),
+        }
+    }
+
+    fn route_to(&mut self, sender: HumanAddr, contract: HumanAddr, msg: Binary) -> StdResult<()> {
+        if contract == HumanAddr::from(BUTTCOIN) {
+            if let Some((recipient, callback)) = self.buttcoin.handle(sender, &msg)? {
+                self.route_to(HumanAddr::from(BUTTCOIN), recipient, callback)?;
+            }
+            Ok(())
+        } else if contract == HumanAddr::from(DISTRIBUTOR) {
+            self.execute_distributor(sender, from_binary(&msg)?)?;
+            Ok(())
+        } else {
+            match self.yield_optimizers.get_mut(&contract) {
+                Some(yield_optimizer) => yield_optimizer.handle(sender, &msg),
+                None => Err(StdError::generic_err(format!(
+                    "No contract at {}.",
+                    contract
+                ))),
+            }
+        }
+    }
+}
+
+// Restores the distributor's storage and the other contracts.
+impl Revert for MockChain {
+    type Snapshot = (
+        MockStorage,
+        MockSnip20,
+        BTreeMap<HumanAddr, MockYieldOptimizer>,
+    );
+
+    fn snapshot(&self) -> Self::Snapshot {
+        (
+            self.distributor.storage.clone(),
+            self.buttcoin.clone(),
+            self.yield_optimizers.clone(),
+        )
+    }
+
+    fn revert(&mut self, (storage, buttcoin, yield_optimizers): Self::Snapshot) {
+        self.distributor.storage = storage;
+        self.buttcoin = buttcoin;
+        self.yield_optimizers = yield_optimizers;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ContractError;
+    use crate::msg::ButtcoinDistributorReceiveMsg;
+    use crate::state::{DeliveryMode, KeeperBounty, VoteAllocation};
+
+    fn register_helper(chain: &mut MockChain, recipient: &SecretContract, weight: u128) {
+        register_with_delivery_mode_helper(chain, recipient, weight, None);
+    }
+
+    fn register_with_delivery_mode_helper(
+        chain: &mut MockChain,
+        recipient: &SecretContract,
+        weight: u128,
+        delivery_mode: Option<DeliveryMode>,
+    ) {
+        chain
+            .execute(
+                ADMIN,
+                ButtcoinDistributorHandleMsg::AddRecipient {
+                    delivery_mode,
+                    recipient: recipient.clone(),
+                    weight: Uint128(weight),
+                },
+            )
+            .unwrap();
+        chain
+            .execute(
+                recipient.address.as_str(),
+                ButtcoinDistributorHandleMsg::AcceptRecipient {
+                    contract_hash: recipient.contract_hash.clone(),
+                },
+            )
+            .unwrap();
+    }
+
+    fn claim_msg() -> ButtcoinDistributorHandleMsg {
+        ButtcoinDistributorHandleMsg::ClaimButtcoin {
+            amount: None,
+            hook: None,
+            recipient: None,
+        }
+    }
+
+    #[test]
+    fn test_fund_advance_and_claim() {
+        let mut chain = MockChain::new(100, 1_100, 10);
+        let vault = chain.add_yield_optimizer("vault");
+        register_helper(&mut chain, &vault, 1);
+
+        // = When the distributor is funded and 1000 blocks pass
+        chain.buttcoin.mint("funder", 10_000);
+        chain.send_buttcoin("funder", 10_000, None).unwrap();
+        chain.advance_blocks(1_000);
+        assert_eq!(
+            chain.query(ButtcoinDistributorQueryMsg::Pending {
+                block: chain.block,
+                recipient: Some(vault.address.clone()),
+            }),
+            ButtcoinDistributorQueryAnswer::Pending {
+                amount: Uint128(10_000)
+            }
+        );
+        // = * It lets the recipient claim everything released into its Buttcoin balance
+        chain.execute("vault", claim_msg()).unwrap();
+        assert_eq!(chain.buttcoin.balance("vault"), 10_000);
+        assert_eq!(chain.buttcoin.balance(DISTRIBUTOR), 0);
+        // = * It deposits through the YieldOptimizer Receive callback
+        assert_eq!(
+            chain.yield_optimizers[&vault.address].deposits,
+            vec![Deposit {
+                amount: Uint128(10_000),
+                from: HumanAddr::from(DISTRIBUTOR),
+                hook: None,
+            }]
+        );
+        // = * It keeps the distributor balance visible with the public viewing key
+        assert_eq!(
+            chain
+                .buttcoin
+                .balance_with_viewing_key(DISTRIBUTOR, VIEWING_KEY)
+                .unwrap(),
+            0
+        );
+
+        // = When claiming again after the schedule has ended
+        // = * It does not pay out anything more
+        chain.advance_blocks(100);
+        chain.execute("vault", claim_msg()).unwrap();
+        assert_eq!(chain.buttcoin.balance("vault"), 10_000);
+    }
+
+    #[test]
+    fn test_delivery_modes() {
+        let mut chain = MockChain::new(100, 1_100, 10);
+        let notify_msg = Binary::from(br#"{"notify":{"amount":"{{amount}}"}}"#.to_vec());
+        let recipients = vec![
+            (
+                chain.add_yield_optimizer("deposit-vault"),
+                DeliveryMode::DepositButtcoin {},
+            ),
+            (
+                chain.add_yield_optimizer("send-vault"),
+                DeliveryMode::Send { msg: None },
+            ),
+            (
+                chain.add_yield_optimizer("notify-vault"),
+                DeliveryMode::SendAndNotify {
+                    msg: notify_msg.clone(),
+                },
+            ),
+            (
+                chain.add_yield_optimizer("transfer-vault"),
+                DeliveryMode::Transfer {},
+            ),
+        ];
+        for (recipient, delivery_mode) in &recipients {
+            register_with_delivery_mode_helper(
+                &mut chain,
+                recipient,
+                1,
+                Some(delivery_mode.clone()),
+            );
+        }
+        chain.buttcoin.mint("funder", 10_000);
+        chain.send_buttcoin("funder", 10_000, None).unwrap();
+        chain.advance_blocks(100);
+        for (recipient, _) in &recipients {
+            chain
+                .execute(recipient.address.as_str(), claim_msg())
+                .unwrap();
+        }
+        let deposit = Deposit {
+            amount: Uint128(250),
+            from: HumanAddr::from(DISTRIBUTOR),
+            hook: None,
+        };
+
+        // = When the recipient is sent a YieldOptimizer deposit
+        // = * It deposits the Buttcoin
+        assert_eq!(chain.buttcoin.balance("deposit-vault"), 250);
+        assert_eq!(
+            chain.yield_optimizers[&HumanAddr::from("deposit-vault")].deposits,
+            vec![deposit.clone()]
+        );
+
+        // = When the recipient is sent Buttcoin without a message
+        // = * It takes it as a plain deposit
+        assert_eq!(chain.buttcoin.balance("send-vault"), 250);
+        assert_eq!(
+            chain.yield_optimizers[&HumanAddr::from("send-vault")].deposits,
+            vec![deposit.clone()]
+        );
+
+        // = When the recipient is sent Buttcoin and then notified
+        // = * It takes the Buttcoin as a plain deposit and executes the notification with the
+        // = amount filled in
+        let notify_vault = &chain.yield_optimizers[&HumanAddr::from("notify-vault")];
+        assert_eq!(chain.buttcoin.balance("notify-vault"), 250);
+        assert_eq!(notify_vault.deposits, vec![deposit]);
+        assert_eq!(
+            notify_vault.notifications,
+            vec![Binary::from(br#"{"notify":{"amount":"250"}}"#.to_vec())]
+        );
+
+        // = When the recipient is transferred Buttcoin
+        // = * It receives the Buttcoin without a callback
+        let transfer_vault = &chain.yield_optimizers[&HumanAddr::from("transfer-vault")];
+        assert_eq!(chain.buttcoin.balance("transfer-vault"), 250);
+        assert_eq!(transfer_vault.deposits, vec![]);
+        assert_eq!(transfer_vault.notifications, Vec::<Binary>::new());
+    }
+
+    #[test]
+    fn test_underfunded_claim() {
+        let mut chain = MockChain::new(100, 1_100, 10);
+        let vault = chain.add_yield_optimizer("vault");
+        register_helper(&mut chain, &vault, 1);
+        chain.buttcoin.mint("funder", 500);
+        chain.send_buttcoin("funder", 500, None).unwrap();
+
+        // = When more has been released than the distributor holds
+        // = * It fails to send
+        // = * It reverts the claim, leaving what the recipient has accrued
+        chain.advance_blocks(100);
+        assert_eq!(
+            chain.execute("vault", claim_msg()).unwrap_err(),
+            StdError::generic_err("insufficient funds: balance=500, required=1000")
+        );
+        assert_eq!(
+            chain.query(ButtcoinDistributorQueryMsg::Pending {
+                block: chain.block,
+                recipient: Some(vault.address.clone()),
+            }),
+            ButtcoinDistributorQueryAnswer::Pending {
+                amount: Uint128(1_000)
+            }
+        );
+        assert_eq!(chain.buttcoin.balance(DISTRIBUTOR), 500);
+    }
+
+    #[test]
+    fn test_distribute_with_fees_and_keeper_bounty() {
+        let mut chain = MockChain::new(100, 1_100, 10);
+        let vault = chain.add_yield_optimizer("vault");
+        let farm = chain.add_yield_optimizer("farm");
+        register_helper(&mut chain, &vault, 1);
+        register_helper(&mut chain, &farm, 3);
+        chain
+            .execute(
+                ADMIN,
+                ButtcoinDistributorHandleMsg::SetFees {
+                    burn_basis_points: 500,
+                    treasury: Some(HumanAddr::from("treasury")),
+                    treasury_basis_points: 1_000,
+                },
+            )
+            .unwrap();
+        chain
+            .execute(
+                ADMIN,
+                ButtcoinDistributorHandleMsg::SetKeeper {
+                    bounty: KeeperBounty::Fixed {
+                        amount: Uint128(50),
+                    },
+                    min_interval: 0,
+                },
+            )
+            .unwrap();
+        chain.buttcoin.mint("funder", 10_000);
+        chain.send_buttcoin("funder", 10_000, None).unwrap();
+
+        // = When a keeper distributes after 100 blocks
+        chain.advance_blocks(100);
+        chain
+            .execute("keeper", ButtcoinDistributorHandleMsg::Distribute {})
+            .unwrap();
+        // = * It splits the release between the recipients, treasury and burn
+        assert_eq!(chain.buttcoin.balance("vault"), 213);
+        assert_eq!(chain.buttcoin.balance("farm"), 638);
+        assert_eq!(chain.buttcoin.balance("treasury"), 100);
+        assert_eq!(chain.buttcoin.total_supply, 10_000 - 49);
+        // = * It pays the keeper its bounty
+        assert_eq!(chain.buttcoin.balance("keeper"), 50);
+        assert_eq!(chain.buttcoin.balance(DISTRIBUTOR), 10_000 - 1_050);
+    }
+
+    #[test]
+    fn test_lock_buttcoin_and_vote() {
+        let mut chain = MockChain::new(100, 1_100, 10);
+        let vault = chain.add_yield_optimizer("vault");
+        let farm = chain.add_yield_optimizer("farm");
+        register_helper(&mut chain, &vault, 1);
+        register_helper(&mut chain, &farm, 1);
+        chain
+            .execute(
+                ADMIN,
+                ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 100 },
+            )
+            .unwrap();
+
+        // = When a holder locks Buttcoin through a send and votes
+        chain.buttcoin.mint("alice", 1_000);
+        chain
+            .send_buttcoin(
+                "alice",
+                1_000,
+                Some(to_binary(&ButtcoinDistributorReceiveMsg::LockButtcoin {}).unwrap()),
+            )
+            .unwrap();
+        chain
+            .execute(
+                "alice",
+                ButtcoinDistributorHandleMsg::Vote {
+                    allocations: vec![VoteAllocation {
+                        basis_points: 10_000,
+                        recipient: vault.address.clone(),
+                    }],
+                },
+            )
+            .unwrap();
+        // = * It holds the locked Buttcoin
+        assert_eq!(chain.buttcoin.balance("alice"), 0);
+        assert_eq!(chain.buttcoin.balance(DISTRIBUTOR), 1_000);
+
+        // = When the next epoch has passed
+        // = * It pays out by the votes
+        chain.buttcoin.mint("funder", 10_000);
+        chain.send_buttcoin("funder", 10_000, None).unwrap();
+        chain.advance_blocks(200);
+        chain.execute("vault", claim_msg()).unwrap();
+        chain.execute("farm", claim_msg()).unwrap();
+        assert_eq!(chain.buttcoin.balance("vault"), 1_500);
+        assert_eq!(chain.buttcoin.balance("farm"), 500);
+    }
+
+    #[test]
+    fn test_locked_buttcoin_is_not_paid_out() {
+        let mut chain = MockChain::new(100, 1_100, 10);
+        let vault = chain.add_yield_optimizer("vault");
+        let farm = chain.add_yield_optimizer("farm");
+        register_helper(&mut chain, &vault, 1);
+        register_helper(&mut chain, &farm, 1);
+        chain
+            .execute(
+                ADMIN,
+                ButtcoinDistributorHandleMsg::EnableGaugeVoting { epoch_length: 100 },
+            )
+            .unwrap();
+        chain.buttcoin.mint("alice", 1_000);
+        chain
+            .send_buttcoin(
+                "alice",
+                1_000,
+                Some(to_binary(&ButtcoinDistributorReceiveMsg::LockButtcoin {}).unwrap()),
+            )
+            .unwrap();
+        let vote_msg = |recipients: Vec<HumanAddr>| ButtcoinDistributorHandleMsg::Vote {
+            allocations: recipients
+                .into_iter()
+                .map(|recipient| VoteAllocation {
+                    basis_points: 10_000,
+                    recipient,
+                })
+                .collect(),
+        };
+        chain
+            .execute("alice", vote_msg(vec![vault.address.clone()]))
+            .unwrap();
+
+        // = When less has been funded than the schedule releases
+        // = * It only pays out the funded Buttcoin
+        chain.buttcoin.mint("funder", 500);
+        chain.send_buttcoin("funder", 500, None).unwrap();
+        chain.advance_blocks(200);
+        chain.execute("vault", claim_msg()).unwrap();
+        chain.execute("farm", claim_msg()).unwrap();
+        assert_eq!(chain.buttcoin.balance("vault"), 250);
+        assert_eq!(chain.buttcoin.balance("farm"), 250);
+        assert_eq!(chain.buttcoin.balance(DISTRIBUTOR), 1_000);
+
+        // = When unlocking in the epoch the votes were withdrawn in
+        // = * It keeps the Buttcoin locked
+        chain.execute("alice", vote_msg(vec![])).unwrap();
+        let unlock_msg = ButtcoinDistributorHandleMsg::UnlockButtcoin {
+            amount: Uint128(1_000),
+        };
+        assert_eq!(
+            chain.execute("alice", unlock_msg.clone()).unwrap_err(),
+            StdError::from(ContractError::UnlockTooEarly)
+        );
+
+        // = When unlocking after that epoch
+        // = * It sends the locked Buttcoin back
+        chain.advance_blocks(100);
+        chain.execute("alice", unlock_msg).unwrap();
+        assert_eq!(chain.buttcoin.balance("alice"), 1_000);
+        assert_eq!(chain.buttcoin.balance(DISTRIBUTOR), 0);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::state::SecretContract;
+use crate::state::{
+    AccrualBucket, DeliveryMode, ExpiryDestination, GaugeWeight, KeeperBounty, Proposal,
+    SecretContract, VoteAllocation,
+};
 use cosmwasm_std::{Binary, HumanAddr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -7,55 +10,311 @@ use serde::{Deserialize, Serialize};
 pub struct InitMsg {
     pub buttcoin: SecretContract,
     pub end_block: u64,
+    pub max_total_emission: Option<Uint128>,
     pub starting_block: u64,
     pub release_per_block: Uint128,
+    pub release_per_block_denominator: Option<u64>,
     pub viewing_key: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ButtcoinDistributorHandleMsg {
+    AcceptRecipient {
+        contract_hash: String,
+    },
+    AddClaimOperator {
+        operator: HumanAddr,
+    },
+    AddRecipient {
+        delivery_mode: Option<DeliveryMode>,
+        recipient: SecretContract,
+        weight: Uint128,
+    },
+    ApproveProposal {
+        id: u64,
+    },
+    ChangeAdmin {
+        admin: HumanAddr,
+    },
     ClaimButtcoin {
+        amount: Option<Uint128>,
+        hook: Option<Binary>,
+        recipient: Option<HumanAddr>,
+    },
+    Distribute {},
+    EnableGaugeVoting {
+        epoch_length: u64,
+    },
+    ExpireRewards {
+        recipients: Vec<HumanAddr>,
+    },
+    Receive {
+        sender: HumanAddr,
+        from: HumanAddr,
+        amount: Uint128,
+        msg: Option<Binary>,
+    },
+    RemoveClaimOperator {
+        operator: HumanAddr,
+    },
+    RenounceAdmin {},
+    ReplaceRecipient {
+        old: HumanAddr,
+        new: SecretContract,
+    },
+    SetClaimLimits {
+        epoch_length: u64,
+        max_per_claim: Option<Uint128>,
+        max_per_epoch: Option<Uint128>,
+    },
+    SetDistribution {
         hook: Option<Binary>,
+        max_recipients_per_call: u64,
+    },
+    SetFees {
+        burn_basis_points: u16,
+        treasury: Option<HumanAddr>,
+        treasury_basis_points: u16,
+    },
+    SetHookValidation {
+        max_size: u64,
+        strict: bool,
+    },
+    SetKeeper {
+        bounty: KeeperBounty,
+        min_interval: u64,
+    },
+    SetMultisig {
+        signers: Vec<HumanAddr>,
+        threshold: u64,
+        proposal_expiry: u64,
     },
     SetReceivableSmartContract {
         receivable_smart_contract: SecretContract,
     },
+    SetRecipientHookVariants {
+        address: HumanAddr,
+        hook_variants: Option<Vec<String>>,
+    },
+    SetRecipientWeight {
+        address: HumanAddr,
+        weight: Uint128,
+    },
+    SetRewardExpiry {
+        bucket_length: u64,
+        destination: ExpiryDestination,
+        expiry_blocks: Option<u64>,
+    },
+    SetSchedule {
+        end_block: u64,
+        max_total_emission: Option<Uint128>,
+        release_per_block: Uint128,
+        release_per_block_denominator: Option<u64>,
+    },
+    UnlockButtcoin {
+        amount: Uint128,
+    },
+    Vote {
+        allocations: Vec<VoteAllocation>,
+    },
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ButtcoinDistributorHandleAnswer {
+    AcceptRecipient {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    AddClaimOperator {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    AddRecipient {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    ApproveProposal {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    ChangeAdmin {
+        status: ButtcoinDistributorResponseStatus,
+    },
     ClaimButtcoin {
+        burned_amount: Uint128,
+        sent_amount: Uint128,
+        status: ButtcoinDistributorResponseStatus,
+        treasury_amount: Uint128,
+    },
+    Distribute {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    EnableGaugeVoting {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    ExpireRewards {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    ProposalCreated {
+        id: u64,
+        status: ButtcoinDistributorResponseStatus,
+    },
+    Receive {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    RemoveClaimOperator {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    RenounceAdmin {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    ReplaceRecipient {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetClaimLimits {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetDistribution {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetFees {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetHookValidation {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetKeeper {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetMultisig {
         status: ButtcoinDistributorResponseStatus,
     },
     SetReceivableSmartContract {
         status: ButtcoinDistributorResponseStatus,
     },
+    SetRecipientHookVariants {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetRecipientWeight {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetRewardExpiry {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    SetSchedule {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    UnlockButtcoin {
+        status: ButtcoinDistributorResponseStatus,
+    },
+    Vote {
+        status: ButtcoinDistributorResponseStatus,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtcoinDistributorReceiveMsg {
+    LockButtcoin {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ButtcoinDistributorQueryMsg {
+    ClaimLimits {},
     Config {},
-    Pending { block: u64 },
+    ExpiringRewards {
+        recipient: HumanAddr,
+    },
+    FeeStats {},
+    GaugeWeights {
+        epoch: u64,
+    },
+    Keeper {},
+    Pending {
+        block: u64,
+        recipient: Option<HumanAddr>,
+    },
+    PendingRecipients {},
+    Proposal {
+        id: u64,
+    },
+    Proposals {},
+    Recipients {},
+    RewardExpiry {},
+    Votes {
+        voter: HumanAddr,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ButtcoinDistributorQueryAnswer {
+    ClaimLimits {
+        epoch_length: u64,
+        max_per_claim: Option<Uint128>,
+        max_per_epoch: Option<Uint128>,
+    },
     Config {
+        admin: HumanAddr,
         buttcoin: SecretContract,
+        emission_headroom: Uint128,
         end_block: u64,
+        frozen: bool,
         last_update_block: u64,
+        max_total_emission: Uint128,
         receivable_smart_contract: Option<SecretContract>,
         release_per_block: Uint128,
+        release_per_block_denominator: u64,
         starting_block: u64,
+        total_funded: Uint128,
         viewing_key: String,
     },
+    ExpiringRewards {
+        accrual_buckets: Vec<AccrualBucket>,
+    },
+    FeeStats {
+        burn_basis_points: u16,
+        total_burned: Uint128,
+        total_treasury: Uint128,
+        treasury: Option<HumanAddr>,
+        treasury_basis_points: u16,
+    },
+    GaugeWeights {
+        epoch: u64,
+        weights: Vec<GaugeWeight>,
+    },
+    Keeper {
+        bounty: Option<KeeperBounty>,
+        last_paid_block: Option<u64>,
+        min_interval: u64,
+        paid_cranks: u64,
+        total_paid: Uint128,
+    },
     Pending {
         amount: Uint128,
     },
+    PendingRecipients {
+        pending_recipients: Vec<PendingRecipientInfo>,
+    },
+    Proposal {
+        proposal: Proposal,
+    },
+    Proposals {
+        proposals: Vec<Proposal>,
+    },
+    Recipients {
+        recipients: Vec<RecipientInfo>,
+    },
+    RewardExpiry {
+        bucket_length: u64,
+        destination: Option<ExpiryDestination>,
+        expiry_blocks: Option<u64>,
+        total_expired: Uint128,
+    },
+    Votes {
+        allocations: Vec<VoteAllocation>,
+        locked: Uint128,
+        locked_in_epoch: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -65,6 +324,31 @@ pub enum ButtcoinDistributorResponseStatus {
     Failure,
 }
 
+// Recipient and PendingRecipient as stored, with the contract address in human form.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRecipientInfo {
+    pub contract: SecretContract,
+    pub delivery_mode: DeliveryMode,
+    pub replaces: Option<HumanAddr>,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecipientInfo {
+    pub accrual_buckets: Vec<AccrualBucket>,
+    pub accrued: Uint128,
+    pub bucketed_until_block: u64,
+    pub claim_epoch: u64,
+    pub claim_operators: Vec<HumanAddr>,
+    pub claimed_in_epoch: Uint128,
+    pub contract: SecretContract,
+    pub delivery_mode: DeliveryMode,
+    pub hook_variants: Option<Vec<String>>,
+    pub reward_debt: Uint128,
+    pub unexpiring: Uint128,
+    pub weight: Uint128,
+}
+
 // === YieldOptimizer ===
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
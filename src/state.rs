@@ -1,9 +1,35 @@
-use cosmwasm_std::{HumanAddr, Storage, Uint128};
-use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
+use crate::msg::ButtcoinDistributorHandleMsg;
+use cosmwasm_std::{Binary, CanonicalAddr, HumanAddr, Storage, Uint128};
+use cosmwasm_storage::{
+    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
+    Singleton,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+pub static CLAIM_LIMITS_KEY: &[u8] = b"claim_limits";
 pub static CONFIG_KEY: &[u8] = b"config";
+pub static DISTRIBUTION_KEY: &[u8] = b"distribution";
+pub static FEES_KEY: &[u8] = b"fees";
+pub static GAUGE_KEY: &[u8] = b"gauge";
+pub static GAUGE_EPOCHS_KEY: &[u8] = b"gauge_epochs";
+pub static HOOK_VALIDATION_KEY: &[u8] = b"hook_validation";
+pub static KEEPER_KEY: &[u8] = b"keeper";
+pub static MULTISIG_KEY: &[u8] = b"multisig";
+pub static PENDING_RECIPIENTS_KEY: &[u8] = b"pending_recipients";
+pub static PROPOSALS_KEY: &[u8] = b"proposals";
+pub static RECIPIENT_ADDRESSES_KEY: &[u8] = b"recipient_addresses";
+pub static RECIPIENTS_KEY: &[u8] = b"recipients";
+pub static REPLACED_RECIPIENTS_KEY: &[u8] = b"replaced_recipients";
+pub static REWARD_EXPIRY_KEY: &[u8] = b"reward_expiry";
+pub static VOTERS_KEY: &[u8] = b"voters";
+
+// A SecretContract whose address was checked and canonicalized on the way in.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, JsonSchema)]
+pub struct CanonicalSecretContract {
+    pub address: CanonicalAddr,
+    pub contract_hash: String,
+}
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, JsonSchema)]
 pub struct SecretContract {
@@ -11,17 +37,249 @@ pub struct SecretContract {
     pub contract_hash: String,
 }
 
+// acc_buttcoin_per_weight is the amount of Buttcoin released per unit of weight since the start,
+// multiplied by REWARD_PRECISION. reward_remainder is what has been left over from dividing by the
+// total weight and from settling recipients, in the same scale.
+// release_per_block / release_per_block_denominator is released every block, counted from
+// schedule_checkpoint_block which moves whenever the schedule changes.
+// unallocated_rewards holds what was released while the total weight was zero, so that it goes to
+// the first recipients instead of being lost.
+// total_emitted is everything released to recipients plus keeper bounties, which can never go over
+// max_total_emission.
+// total_funded is the Buttcoin received without a message. Once gauge_voting is enabled the
+// distributor also holds locked Buttcoin, so total_emitted can't go over total_funded either.
+// buttcoin and receivable_smart_contract are stored with canonical addresses.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
-    pub buttcoin: SecretContract,
+    pub acc_buttcoin_per_weight: Uint128,
+    pub admin: HumanAddr,
+    pub buttcoin: CanonicalSecretContract,
     pub end_block: u64,
+    pub frozen: bool,
+    pub gauge_voting: bool,
     pub last_update_block: u64,
-    pub receivable_smart_contract: Option<SecretContract>,
+    pub max_total_emission: Uint128,
+    pub receivable_smart_contract: Option<CanonicalSecretContract>,
     pub release_per_block: Uint128,
+    pub release_per_block_denominator: u64,
+    pub reward_remainder: Uint128,
+    pub schedule_checkpoint_block: u64,
     pub starting_block: u64,
+    pub total_emitted: Uint128,
+    pub total_funded: Uint128,
+    pub total_weight: Uint128,
+    pub unallocated_rewards: Uint128,
     pub viewing_key: String,
 }
 
+// accrual_buckets hold the accrued Buttcoin that has been put up for expiry, oldest first.
+// bucketed_until_block is the block up to which accrued Buttcoin has been put in buckets.
+// accrued is what has been settled for the recipient but not claimed yet.
+// claimed_in_epoch is what has been paid out to the recipient in claim_epoch of the claim limits.
+// claim_operators can claim on behalf of the recipient, with the payout still going to the
+// recipient.
+// delivery_mode is how payouts are sent to the recipient.
+// hook_variants restricts the hooks the recipient can claim with to these variants in strict mode,
+// instead of the YieldOptimizer ones.
+// reward_debt is weight * acc_buttcoin_per_weight at the time of the last settlement, multiplied by
+// REWARD_PRECISION.
+// unexpiring is the accrued Buttcoin that was earned while expiry was off, which is never put in
+// buckets.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Recipient {
+    pub accrual_buckets: Vec<AccrualBucket>,
+    pub accrued: Uint128,
+    pub bucketed_until_block: u64,
+    pub claim_epoch: u64,
+    pub claim_operators: Vec<HumanAddr>,
+    pub claimed_in_epoch: Uint128,
+    pub contract: CanonicalSecretContract,
+    pub delivery_mode: DeliveryMode,
+    pub hook_variants: Option<Vec<String>>,
+    pub reward_debt: Uint128,
+    pub unexpiring: Uint128,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccrualBucket {
+    pub amount: Uint128,
+    pub expires_at_block: u64,
+}
+
+// A registration that only becomes a Recipient once the contract accepts it.
+// replaces is the recipient that the contract takes over from when it accepts, in which case weight
+// is the weight the old recipient had when the replacement was registered.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRecipient {
+    pub contract: CanonicalSecretContract,
+    pub delivery_mode: DeliveryMode,
+    pub replaces: Option<HumanAddr>,
+    pub weight: Uint128,
+}
+
+// Caps on what a recipient is paid per claim and per epoch of epoch_length blocks from the starting
+// block. Whatever is over the caps stays accrued for later.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ClaimLimits {
+    pub epoch_length: u64,
+    pub max_per_claim: Option<Uint128>,
+    pub max_per_epoch: Option<Uint128>,
+}
+
+// cursor is the index in the recipient addresses that the next Distribute starts from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Distribution {
+    pub cursor: u64,
+    pub hook: Option<Binary>,
+    pub max_recipients_per_call: u64,
+}
+
+// Shares of every payout that go to the treasury and get burned, along with the totals so far.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Fees {
+    pub burn_basis_points: u16,
+    pub total_burned: Uint128,
+    pub total_treasury: Uint128,
+    pub treasury: Option<HumanAddr>,
+    pub treasury_basis_points: u16,
+}
+
+// epoch is the epoch whose weights are currently applied.
+// tally is the live vote count per recipient, which becomes the weights at the next epoch boundary.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Gauge {
+    pub epoch: u64,
+    pub epoch_length: u64,
+    pub latest_snapshot_epoch: u64,
+    pub tally: Vec<GaugeWeight>,
+    pub total_locked: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeEpoch {
+    pub epoch: u64,
+    pub previous_snapshot_epoch: Option<u64>,
+    pub weights: Vec<GaugeWeight>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeWeight {
+    pub recipient: HumanAddr,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteAllocation {
+    pub basis_points: u16,
+    pub recipient: HumanAddr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Voter {
+    pub allocations: Vec<VoteAllocation>,
+    pub locked: Uint128,
+    pub locked_in_epoch: u64,
+}
+
+// DepositButtcoin sends with the YieldOptimizer DepositButtcoin message and the claim's hook.
+// Send sends with msg and SendAndNotify sends without a message before executing msg on the
+// recipient. {{amount}} in msg is replaced with the amount sent.
+// Transfer transfers without any callback.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryMode {
+    DepositButtcoin {},
+    Send { msg: Option<Binary> },
+    SendAndNotify { msg: Binary },
+    Transfer {},
+}
+
+impl Default for DeliveryMode {
+    fn default() -> Self {
+        DeliveryMode::DepositButtcoin {}
+    }
+}
+
+// In strict mode hooks are decoded and checked against max_size before being forwarded.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct HookValidation {
+    pub max_size: u64,
+    pub strict: bool,
+}
+
+// Bounty paid to whoever calls Distribute, at most once every min_interval blocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Keeper {
+    pub bounty: KeeperBounty,
+    pub last_paid_block: Option<u64>,
+    pub min_interval: u64,
+    pub paid_cranks: u64,
+    pub total_paid: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KeeperBounty {
+    BasisPoints { basis_points: u16, cap: Uint128 },
+    Fixed { amount: Uint128 },
+}
+
+// Accrued Buttcoin is put in the bucket of bucket_length blocks it was earned in, and is forfeited
+// expiry_blocks after the end of that bucket. None for expiry_blocks turns expiry off.
+// counted_from_block is when expiry was turned on, as Buttcoin earned before then never expires.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardExpiry {
+    pub bucket_length: u64,
+    pub counted_from_block: u64,
+    pub destination: ExpiryDestination,
+    pub expiry_blocks: Option<u64>,
+    pub total_expired: Uint128,
+}
+
+// Redistribute spreads forfeited Buttcoin over all recipients by weight, Treasury transfers it to
+// address.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryDestination {
+    Redistribute {},
+    Treasury { address: HumanAddr },
+}
+
+// Signer set that privileged handles have to go through once enabled.
+// An empty signer set means the admin acts alone.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Multisig {
+    pub pending_proposal_ids: Vec<u64>,
+    pub proposal_count: u64,
+    pub proposal_expiry: u64,
+    pub signers: Vec<HumanAddr>,
+    pub threshold: u64,
+}
+
+impl Multisig {
+    pub fn is_enabled(&self) -> bool {
+        !self.signers.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub approvals: Vec<HumanAddr>,
+    pub executed: bool,
+    pub expires_at_block: u64,
+    pub id: u64,
+    pub msg: ButtcoinDistributorHandleMsg,
+}
+
+pub fn claim_limits_config<S: Storage>(storage: &mut S) -> Singleton<S, ClaimLimits> {
+    singleton(storage, CLAIM_LIMITS_KEY)
+}
+
+pub fn claim_limits_config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, ClaimLimits> {
+    singleton_read(storage, CLAIM_LIMITS_KEY)
+}
+
 pub fn config<S: Storage>(storage: &mut S) -> Singleton<S, State> {
     singleton(storage, CONFIG_KEY)
 }
@@ -29,3 +287,120 @@ pub fn config<S: Storage>(storage: &mut S) -> Singleton<S, State> {
 pub fn config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, State> {
     singleton_read(storage, CONFIG_KEY)
 }
+
+pub fn distribution_config<S: Storage>(storage: &mut S) -> Singleton<S, Distribution> {
+    singleton(storage, DISTRIBUTION_KEY)
+}
+
+pub fn distribution_config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Distribution> {
+    singleton_read(storage, DISTRIBUTION_KEY)
+}
+
+pub fn fees_config<S: Storage>(storage: &mut S) -> Singleton<S, Fees> {
+    singleton(storage, FEES_KEY)
+}
+
+pub fn fees_config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Fees> {
+    singleton_read(storage, FEES_KEY)
+}
+
+pub fn gauge_config<S: Storage>(storage: &mut S) -> Singleton<S, Gauge> {
+    singleton(storage, GAUGE_KEY)
+}
+
+pub fn gauge_config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Gauge> {
+    singleton_read(storage, GAUGE_KEY)
+}
+
+pub fn gauge_epochs<S: Storage>(storage: &mut S) -> Bucket<S, GaugeEpoch> {
+    bucket(GAUGE_EPOCHS_KEY, storage)
+}
+
+pub fn gauge_epochs_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, GaugeEpoch> {
+    bucket_read(GAUGE_EPOCHS_KEY, storage)
+}
+
+pub fn hook_validation_config<S: Storage>(storage: &mut S) -> Singleton<S, HookValidation> {
+    singleton(storage, HOOK_VALIDATION_KEY)
+}
+
+pub fn hook_validation_config_read<S: Storage>(
+    storage: &S,
+) -> ReadonlySingleton<S, HookValidation> {
+    singleton_read(storage, HOOK_VALIDATION_KEY)
+}
+
+pub fn keeper_config<S: Storage>(storage: &mut S) -> Singleton<S, Keeper> {
+    singleton(storage, KEEPER_KEY)
+}
+
+pub fn keeper_config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Keeper> {
+    singleton_read(storage, KEEPER_KEY)
+}
+
+pub fn multisig_config<S: Storage>(storage: &mut S) -> Singleton<S, Multisig> {
+    singleton(storage, MULTISIG_KEY)
+}
+
+pub fn multisig_config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Multisig> {
+    singleton_read(storage, MULTISIG_KEY)
+}
+
+pub fn pending_recipients<S: Storage>(storage: &mut S) -> Singleton<S, Vec<PendingRecipient>> {
+    singleton(storage, PENDING_RECIPIENTS_KEY)
+}
+
+pub fn pending_recipients_read<S: Storage>(
+    storage: &S,
+) -> ReadonlySingleton<S, Vec<PendingRecipient>> {
+    singleton_read(storage, PENDING_RECIPIENTS_KEY)
+}
+
+pub fn proposals<S: Storage>(storage: &mut S) -> Bucket<S, Proposal> {
+    bucket(PROPOSALS_KEY, storage)
+}
+
+pub fn proposals_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Proposal> {
+    bucket_read(PROPOSALS_KEY, storage)
+}
+
+pub fn recipient_addresses<S: Storage>(storage: &mut S) -> Singleton<S, Vec<HumanAddr>> {
+    singleton(storage, RECIPIENT_ADDRESSES_KEY)
+}
+
+pub fn recipient_addresses_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Vec<HumanAddr>> {
+    singleton_read(storage, RECIPIENT_ADDRESSES_KEY)
+}
+
+pub fn recipients<S: Storage>(storage: &mut S) -> Bucket<S, Recipient> {
+    bucket(RECIPIENTS_KEY, storage)
+}
+
+pub fn recipients_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Recipient> {
+    bucket_read(RECIPIENTS_KEY, storage)
+}
+
+// Maps the address of a replaced recipient to the address that replaced it.
+pub fn replaced_recipients<S: Storage>(storage: &mut S) -> Bucket<S, HumanAddr> {
+    bucket(REPLACED_RECIPIENTS_KEY, storage)
+}
+
+pub fn replaced_recipients_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, HumanAddr> {
+    bucket_read(REPLACED_RECIPIENTS_KEY, storage)
+}
+
+pub fn reward_expiry_config<S: Storage>(storage: &mut S) -> Singleton<S, RewardExpiry> {
+    singleton(storage, REWARD_EXPIRY_KEY)
+}
+
+pub fn reward_expiry_config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, RewardExpiry> {
+    singleton_read(storage, REWARD_EXPIRY_KEY)
+}
+
+pub fn voters<S: Storage>(storage: &mut S) -> Bucket<S, Voter> {
+    bucket(VOTERS_KEY, storage)
+}
+
+pub fn voters_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, Voter> {
+    bucket_read(VOTERS_KEY, storage)
+}
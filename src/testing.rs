@@ -0,0 +1,91 @@
+// Helpers for running the contract against mock dependencies instead of a chain, shared by the mock
+// chain, the simulate example and the fuzz targets. Left out of the contract's wasm.
+use cosmwasm_std::{
+    from_binary, Api, Binary, CosmosMsg, Extern, HumanAddr, Querier, StdResult, Storage, Uint128,
+    WasmMsg,
+};
+use serde::Deserialize;
+
+// The parts of the SNIP-20 handle messages that the distributor sends.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Snip20HandleMsg {
+    Burn {
+        amount: Uint128,
+    },
+    RegisterReceive {},
+    Send {
+        recipient: HumanAddr,
+        amount: Uint128,
+        msg: Option<Binary>,
+    },
+    SetViewingKey {
+        key: String,
+    },
+    Transfer {
+        recipient: HumanAddr,
+        amount: Uint128,
+    },
+}
+
+// What an execution can change, so that it can be rolled back the way the chain reverts a failed
+// transaction.
+pub trait Revert {
+    type Snapshot;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    fn revert(&mut self, snapshot: Self::Snapshot);
+}
+
+impl<S: Storage + Clone, A: Api, Q: Querier> Revert for Extern<S, A, Q> {
+    type Snapshot = S;
+
+    fn snapshot(&self) -> S {
+        self.storage.clone()
+    }
+
+    fn revert(&mut self, snapshot: S) {
+        self.storage = snapshot;
+    }
+}
+
+// Reverts everything that execute did when it fails.
+pub fn execute_or_revert<R: Revert, T>(
+    target: &mut R,
+    execute: impl FnOnce(&mut R) -> StdResult<T>,
+) -> StdResult<T> {
+    let snapshot = target.snapshot();
+    let result = execute(target);
+    if result.is_err() {
+        target.revert(snapshot);
+    }
+
+    result
+}
+
+// Buttcoin that leaves the distributor through the messages, whether it is sent, transferred or
+// burned.
+pub fn buttcoin_paid_out(buttcoin: &HumanAddr, messages: &[CosmosMsg]) -> StdResult<u128> {
+    let mut paid_out: u128 = 0;
+    for message in messages {
+        if let CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }) = message
+        {
+            if contract_addr == buttcoin {
+                let amount = match from_binary(msg)? {
+                    Snip20HandleMsg::Burn { amount }
+                    | Snip20HandleMsg::Send { amount, .. }
+                    | Snip20HandleMsg::Transfer { amount, .. } => amount.u128(),
+                    Snip20HandleMsg::RegisterReceive {} | Snip20HandleMsg::SetViewingKey { .. } => {
+                        0
+                    }
+                };
+                paid_out = paid_out.saturating_add(amount);
+            }
+        }
+    }
+
+    Ok(paid_out)
+}
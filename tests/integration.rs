@@ -1,18 +1,346 @@
-//! This integration test tries to run and call the generated wasm.
-//! It depends on a Wasm build being available, which you can create with `cargo wasm`.
-//! Then running `cargo integration-test` will validate we can properly call into that generated Wasm.
+//! Runs the compiled contract in cosmwasm-vm, so the wasm has to be built with `cargo wasm` before
+//! running `cargo integration-test`. The tests are ignored by a plain `cargo test`, which doesn't
+//! build the wasm.
 //!
-//! You can easily convert unit tests to integration tests.
-//! 1. First copy them over verbatum,
-//! 2. Then change
-//!      let mut deps = mock_dependencies(20, &[]);
-//!    to
-//!      let mut deps = mock_instance(WASM, &[]);
-//! 3. If you access raw storage, where ever you see something like:
-//!      deps.storage.get(CONFIG_KEY).expect("no data stored");
-//!    replace it with:
-//!      deps.with_storage(|store| {
-//!          let data = store.get(CONFIG_KEY).expect("no data stored");
-//!          //...
-//!      });
-//! 4. Anywhere you see query(&deps, ...) you must replace it with query(&mut deps, ...)
+//! It checks the functions the wasm exports and runs init, adding and accepting a recipient,
+//! claiming, changing a recipient's weight and the pending query, checking the gas each of them
+//! uses against what is recorded in tests/gas_baseline.toml.
+
+use cosmwasm_std::{
+    from_binary, Env, HandleResponse, HumanAddr, InitResponse, StdError, StdResult, Uint128,
+};
+use cosmwasm_vm::testing::{
+    handle, init, mock_env, mock_instance_with_gas_limit, query, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_vm::Instance;
+use cw_buttcoin_distributor::msg::{
+    ButtcoinDistributorHandleMsg, ButtcoinDistributorQueryAnswer, ButtcoinDistributorQueryMsg,
+    InitMsg,
+};
+use cw_buttcoin_distributor::state::SecretContract;
+
+const WASM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/target/wasm32-unknown-unknown/release/cw_buttcoin_distributor.wasm"
+);
+
+// Each handler can use up to GAS_MARGIN_PERCENT more gas than is recorded for it in
+// tests/gas_baseline.toml. After a change that is meant to make a handler more expensive, record
+// the gas that the failing test reports. A handler without a recorded entry is reported instead
+// of checked.
+const GAS_BASELINE: &str = include_str!("gas_baseline.toml");
+const GAS_LIMIT: u64 = 500_000_000_000;
+const GAS_MARGIN_PERCENT: u64 = 10;
+
+const MOCK_ADMIN: &str = "admin";
+
+type MockInstance = Instance<MockStorage, MockApi, MockQuerier>;
+
+// =========== HELPERS ===========
+
+fn wasm() -> Vec<u8> {
+    std::fs::read(WASM_PATH).unwrap_or_else(|error| {
+        panic!(
+            "could not read the wasm at {}: {}. Build it with `cargo wasm` first.",
+            WASM_PATH, error
+        )
+    })
+}
+
+fn mock_buttcoin() -> SecretContract {
+    SecretContract {
+        address: HumanAddr::from("buttcoin-address"),
+        contract_hash: "b".repeat(64),
+    }
+}
+
+fn mock_yield_optimizer_smart_contract() -> SecretContract {
+    SecretContract {
+        address: HumanAddr::from("yield-optimizer"),
+        contract_hash: "c".repeat(64),
+    }
+}
+
+fn mock_env_at_block(sender: &str, height: u64) -> Env {
+    let mut env = mock_env(sender, &[]);
+    env.block.height = height;
+    env
+}
+
+fn gas_used<T>(deps: &mut MockInstance, action: impl FnOnce(&mut MockInstance) -> T) -> (T, u64) {
+    let gas_before = deps.get_gas_left();
+    let result = action(deps);
+    (result, gas_before - deps.get_gas_left())
+}
+
+fn assert_gas_within_baseline(handler: &str, gas: u64) {
+    let baseline: toml::value::Table = toml::from_str(GAS_BASELINE).unwrap();
+    let recorded = match baseline.get(handler).and_then(toml::Value::as_integer) {
+        Some(recorded) => recorded as u64,
+        None => {
+            eprintln!(
+                "no gas is recorded for {} in tests/gas_baseline.toml, so it isn't checked. It \
+                 used {} gas, record `{} = {}` to check it.",
+                handler, gas, handler, gas
+            );
+            return;
+        }
+    };
+    assert!(
+        gas <= recorded + recorded * GAS_MARGIN_PERCENT / 100,
+        "{} used {} gas, more than {}% over the {} recorded",
+        handler,
+        gas,
+        GAS_MARGIN_PERCENT,
+        recorded
+    );
+}
+
+fn init_helper(deps: &mut MockInstance) -> StdResult<InitResponse> {
+    let msg = InitMsg {
+        buttcoin: mock_buttcoin(),
+        end_block: 1_000,
+        max_total_emission: None,
+        release_per_block: Uint128(100),
+        release_per_block_denominator: None,
+        starting_block: 100,
+        viewing_key: "viewing-key".to_string(),
+    };
+    init(deps, mock_env_at_block(MOCK_ADMIN, 100), msg)
+}
+
+fn handle_helper(
+    deps: &mut MockInstance,
+    sender: &str,
+    height: u64,
+    msg: ButtcoinDistributorHandleMsg,
+) -> StdResult<HandleResponse> {
+    handle(deps, mock_env_at_block(sender, height), msg)
+}
+
+fn pending_helper(deps: &mut MockInstance, block: u64) -> Uint128 {
+    let res = query(
+        deps,
+        ButtcoinDistributorQueryMsg::Pending {
+            block,
+            recipient: Some(mock_yield_optimizer_smart_contract().address),
+        },
+    )
+    .unwrap();
+    match from_binary(&res).unwrap() {
+        ButtcoinDistributorQueryAnswer::Pending { amount } => amount,
+        _ => panic!("unexpected error"),
+    }
+}
+
+fn register_helper(deps: &mut MockInstance, weight: u128) {
+    let recipient = mock_yield_optimizer_smart_contract();
+    handle_helper(
+        deps,
+        MOCK_ADMIN,
+        100,
+        ButtcoinDistributorHandleMsg::AddRecipient {
+            delivery_mode: None,
+            recipient: recipient.clone(),
+            weight: Uint128(weight),
+        },
+    )
+    .unwrap();
+    handle_helper(
+        deps,
+        recipient.address.as_str(),
+        100,
+        ButtcoinDistributorHandleMsg::AcceptRecipient {
+            contract_hash: recipient.contract_hash,
+        },
+    )
+    .unwrap();
+}
+
+// Names of the functions exported by a wasm module, read straight from its export section.
+fn exported_functions(wasm: &[u8]) -> Vec<String> {
+    fn read_u32(wasm: &[u8], offset: &mut usize) -> u32 {
+        let mut result = 0;
+        let mut shift = 0;
+        loop {
+            let byte = wasm[*offset];
+            *offset += 1;
+            result |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return result;
+            }
+            shift += 7;
+        }
+    }
+
+    assert_eq!(&wasm[0..4], b"\0asm", "not a wasm module");
+    let mut offset = 8;
+    let mut exports = vec![];
+    while offset < wasm.len() {
+        let section_id = wasm[offset];
+        offset += 1;
+        let section_size = read_u32(wasm, &mut offset) as usize;
+        let section_end = offset + section_size;
+        if section_id == 7 {
+            let count = read_u32(wasm, &mut offset);
+            for _ in 0..count {
+                let name_length = read_u32(wasm, &mut offset) as usize;
+                let name = String::from_utf8(wasm[offset..offset + name_length].to_vec()).unwrap();
+                offset += name_length;
+                let kind = wasm[offset];
+                offset += 1;
+                read_u32(wasm, &mut offset);
+                if kind == 0 {
+                    exports.push(name);
+                }
+            }
+        }
+        offset = section_end;
+    }
+
+    exports
+}
+
+// =========== TESTS ===========
+
+#[test]
+#[ignore]
+fn test_export_surface() {
+    // = When the wasm is built
+    // = * It exports the entry points, no migrate and nothing else besides what cosmwasm-std adds
+    let mut exports = exported_functions(&wasm());
+    exports.sort();
+    assert_eq!(
+        exports,
+        vec![
+            "allocate",
+            "cosmwasm_vm_version_3",
+            "deallocate",
+            "handle",
+            "init",
+            "query"
+        ],
+        "the exported functions have changed"
+    );
+}
+
+#[test]
+#[ignore]
+fn test_init() {
+    let mut deps = mock_instance_with_gas_limit(&wasm(), GAS_LIMIT);
+
+    // = When initialized
+    // = * It registers with Buttcoin and sets the viewing key within the gas ceiling
+    let (res, gas) = gas_used(&mut deps, |deps| init_helper(deps));
+    assert_eq!(res.unwrap().messages.len(), 2);
+    assert_gas_within_baseline("init", gas);
+}
+
+#[test]
+#[ignore]
+fn test_add_recipient_and_pending() {
+    let mut deps = mock_instance_with_gas_limit(&wasm(), GAS_LIMIT);
+    init_helper(&mut deps).unwrap();
+    let recipient = mock_yield_optimizer_smart_contract();
+
+    // = When a recipient is added by someone other than the admin
+    // = * It raises an error
+    assert_eq!(
+        handle_helper(
+            &mut deps,
+            "user",
+            100,
+            ButtcoinDistributorHandleMsg::AddRecipient {
+                delivery_mode: None,
+                recipient: recipient.clone(),
+                weight: Uint128(1),
+            },
+        )
+        .unwrap_err(),
+        StdError::unauthorized()
+    );
+
+    // = When a recipient is added by the admin and accepts
+    // = * It earns the release from then on within the gas ceilings
+    let (res, gas) = gas_used(&mut deps, |deps| {
+        handle_helper(
+            deps,
+            MOCK_ADMIN,
+            100,
+            ButtcoinDistributorHandleMsg::AddRecipient {
+                delivery_mode: None,
+                recipient: recipient.clone(),
+                weight: Uint128(1),
+            },
+        )
+    });
+    res.unwrap();
+    assert_gas_within_baseline("add_recipient", gas);
+    let (res, gas) = gas_used(&mut deps, |deps| {
+        handle_helper(
+            deps,
+            recipient.address.as_str(),
+            100,
+            ButtcoinDistributorHandleMsg::AcceptRecipient {
+                contract_hash: recipient.contract_hash.clone(),
+            },
+        )
+    });
+    res.unwrap();
+    assert_gas_within_baseline("accept_recipient", gas);
+    let (pending, gas) = gas_used(&mut deps, |deps| pending_helper(deps, 110));
+    assert_eq!(pending, Uint128(1_000));
+    assert_gas_within_baseline("pending", gas);
+}
+
+#[test]
+#[ignore]
+fn test_claim_buttcoin() {
+    let mut deps = mock_instance_with_gas_limit(&wasm(), GAS_LIMIT);
+    init_helper(&mut deps).unwrap();
+    register_helper(&mut deps, 1);
+    let recipient = mock_yield_optimizer_smart_contract();
+
+    // = When the recipient claims
+    // = * It sends what has accrued within the gas ceiling
+    let (res, gas) = gas_used(&mut deps, |deps| {
+        handle_helper(
+            deps,
+            recipient.address.as_str(),
+            110,
+            ButtcoinDistributorHandleMsg::ClaimButtcoin {
+                amount: None,
+                hook: None,
+                recipient: None,
+            },
+        )
+    });
+    assert_eq!(res.unwrap().messages.len(), 1);
+    assert_gas_within_baseline("claim_buttcoin", gas);
+    assert_eq!(pending_helper(&mut deps, 110), Uint128(0));
+}
+
+#[test]
+#[ignore]
+fn test_set_recipient_weight() {
+    let mut deps = mock_instance_with_gas_limit(&wasm(), GAS_LIMIT);
+    init_helper(&mut deps).unwrap();
+    register_helper(&mut deps, 1);
+
+    // = When the admin changes the weight of the only recipient
+    // = * It keeps what was earned and keeps earning the whole release within the gas ceiling
+    let (res, gas) = gas_used(&mut deps, |deps| {
+        handle_helper(
+            deps,
+            MOCK_ADMIN,
+            110,
+            ButtcoinDistributorHandleMsg::SetRecipientWeight {
+                address: mock_yield_optimizer_smart_contract().address,
+                weight: Uint128(5),
+            },
+        )
+    });
+    res.unwrap();
+    assert_gas_within_baseline("set_recipient_weight", gas);
+    assert_eq!(pending_helper(&mut deps, 120), Uint128(2_000));
+}
@@ -0,0 +1,53 @@
+// Fails when the committed schema/*.json or ts/*.ts no longer match the Rust types. Run
+// `cargo schema` to bring them up to date.
+#[path = "../examples/schema.rs"]
+mod schema;
+
+use std::collections::BTreeMap;
+use std::fs::{read_dir, read_to_string, remove_dir_all};
+use std::path::Path;
+
+fn files(dir: &Path) -> BTreeMap<String, String> {
+    read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            (
+                path.file_name().unwrap().to_string_lossy().to_string(),
+                read_to_string(&path).unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_committed_schemas_are_up_to_date() {
+    let out_dir = std::env::temp_dir().join(format!(
+        "buttcoin-distributor-schema-{}",
+        std::process::id()
+    ));
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    // = When the schemas are generated from the Rust types
+    // = * They match the committed ones
+    schema::export_schemas(&out_dir.join("schema"), &out_dir.join("ts"));
+    for dir in &["schema", "ts"] {
+        let generated = files(&out_dir.join(dir));
+        let committed = files(&root.join(dir));
+        assert_eq!(
+            generated.keys().collect::<Vec<_>>(),
+            committed.keys().collect::<Vec<_>>(),
+            "{}/ has different files than the Rust types generate, run `cargo schema`",
+            dir
+        );
+        for (name, contents) in &generated {
+            assert!(
+                &committed[name] == contents,
+                "{}/{} is out of date, run `cargo schema`",
+                dir,
+                name
+            );
+        }
+    }
+    remove_dir_all(out_dir).unwrap();
+}
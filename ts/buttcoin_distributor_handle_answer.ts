@@ -0,0 +1,119 @@
+// Generated from schema/buttcoin_distributor_handle_answer.json by `cargo schema`. Do not edit by hand.
+
+export type ButtcoinDistributorHandleAnswer = {
+  accept_recipient: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  add_claim_operator: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  add_recipient: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  approve_proposal: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  change_admin: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  claim_buttcoin: {
+    burned_amount: Uint128;
+    sent_amount: Uint128;
+    status: ButtcoinDistributorResponseStatus;
+    treasury_amount: Uint128;
+  };
+} | {
+  distribute: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  enable_gauge_voting: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  expire_rewards: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  proposal_created: {
+    id: number;
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  receive: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  remove_claim_operator: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  renounce_admin: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  replace_recipient: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_claim_limits: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_distribution: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_fees: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_hook_validation: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_keeper: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_multisig: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_receivable_smart_contract: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_recipient_hook_variants: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_recipient_weight: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_reward_expiry: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  set_schedule: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  unlock_buttcoin: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+} | {
+  vote: {
+    status: ButtcoinDistributorResponseStatus;
+  };
+};
+
+export type ButtcoinDistributorResponseStatus = "success" | "failure";
+
+export type Uint128 = string;
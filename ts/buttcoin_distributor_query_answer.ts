@@ -0,0 +1,298 @@
+// Generated from schema/buttcoin_distributor_query_answer.json by `cargo schema`. Do not edit by hand.
+
+export type ButtcoinDistributorQueryAnswer = {
+  claim_limits: {
+    epoch_length: number;
+    max_per_claim?: Uint128 | null;
+    max_per_epoch?: Uint128 | null;
+  };
+} | {
+  config: {
+    admin: HumanAddr;
+    buttcoin: SecretContract;
+    emission_headroom: Uint128;
+    end_block: number;
+    frozen: boolean;
+    last_update_block: number;
+    max_total_emission: Uint128;
+    receivable_smart_contract?: SecretContract | null;
+    release_per_block: Uint128;
+    release_per_block_denominator: number;
+    starting_block: number;
+    total_funded: Uint128;
+    viewing_key: string;
+  };
+} | {
+  expiring_rewards: {
+    accrual_buckets: AccrualBucket[];
+  };
+} | {
+  fee_stats: {
+    burn_basis_points: number;
+    total_burned: Uint128;
+    total_treasury: Uint128;
+    treasury?: HumanAddr | null;
+    treasury_basis_points: number;
+  };
+} | {
+  gauge_weights: {
+    epoch: number;
+    weights: GaugeWeight[];
+  };
+} | {
+  keeper: {
+    bounty?: KeeperBounty | null;
+    last_paid_block?: number | null;
+    min_interval: number;
+    paid_cranks: number;
+    total_paid: Uint128;
+  };
+} | {
+  pending: {
+    amount: Uint128;
+  };
+} | {
+  pending_recipients: {
+    pending_recipients: PendingRecipientInfo[];
+  };
+} | {
+  proposal: {
+    proposal: Proposal;
+  };
+} | {
+  proposals: {
+    proposals: Proposal[];
+  };
+} | {
+  recipients: {
+    recipients: RecipientInfo[];
+  };
+} | {
+  reward_expiry: {
+    bucket_length: number;
+    destination?: ExpiryDestination | null;
+    expiry_blocks?: number | null;
+    total_expired: Uint128;
+  };
+} | {
+  votes: {
+    allocations: VoteAllocation[];
+    locked: Uint128;
+    locked_in_epoch: number;
+  };
+};
+
+export type AccrualBucket = {
+  amount: Uint128;
+  expires_at_block: number;
+};
+
+export type Binary = string;
+
+export type ButtcoinDistributorHandleMsg = {
+  accept_recipient: {
+    contract_hash: string;
+  };
+} | {
+  add_claim_operator: {
+    operator: HumanAddr;
+  };
+} | {
+  add_recipient: {
+    delivery_mode?: DeliveryMode | null;
+    recipient: SecretContract;
+    weight: Uint128;
+  };
+} | {
+  approve_proposal: {
+    id: number;
+  };
+} | {
+  change_admin: {
+    admin: HumanAddr;
+  };
+} | {
+  claim_buttcoin: {
+    amount?: Uint128 | null;
+    hook?: Binary | null;
+    recipient?: HumanAddr | null;
+  };
+} | {
+  distribute: Record<string, never>;
+} | {
+  enable_gauge_voting: {
+    epoch_length: number;
+  };
+} | {
+  expire_rewards: {
+    recipients: HumanAddr[];
+  };
+} | {
+  receive: {
+    amount: Uint128;
+    from: HumanAddr;
+    msg?: Binary | null;
+    sender: HumanAddr;
+  };
+} | {
+  remove_claim_operator: {
+    operator: HumanAddr;
+  };
+} | {
+  renounce_admin: Record<string, never>;
+} | {
+  replace_recipient: {
+    new: SecretContract;
+    old: HumanAddr;
+  };
+} | {
+  set_claim_limits: {
+    epoch_length: number;
+    max_per_claim?: Uint128 | null;
+    max_per_epoch?: Uint128 | null;
+  };
+} | {
+  set_distribution: {
+    hook?: Binary | null;
+    max_recipients_per_call: number;
+  };
+} | {
+  set_fees: {
+    burn_basis_points: number;
+    treasury?: HumanAddr | null;
+    treasury_basis_points: number;
+  };
+} | {
+  set_hook_validation: {
+    max_size: number;
+    strict: boolean;
+  };
+} | {
+  set_keeper: {
+    bounty: KeeperBounty;
+    min_interval: number;
+  };
+} | {
+  set_multisig: {
+    proposal_expiry: number;
+    signers: HumanAddr[];
+    threshold: number;
+  };
+} | {
+  set_receivable_smart_contract: {
+    receivable_smart_contract: SecretContract;
+  };
+} | {
+  set_recipient_hook_variants: {
+    address: HumanAddr;
+    hook_variants?: string[] | null;
+  };
+} | {
+  set_recipient_weight: {
+    address: HumanAddr;
+    weight: Uint128;
+  };
+} | {
+  set_reward_expiry: {
+    bucket_length: number;
+    destination: ExpiryDestination;
+    expiry_blocks?: number | null;
+  };
+} | {
+  set_schedule: {
+    end_block: number;
+    max_total_emission?: Uint128 | null;
+    release_per_block: Uint128;
+    release_per_block_denominator?: number | null;
+  };
+} | {
+  unlock_buttcoin: {
+    amount: Uint128;
+  };
+} | {
+  vote: {
+    allocations: VoteAllocation[];
+  };
+};
+
+export type DeliveryMode = {
+  deposit_buttcoin: Record<string, never>;
+} | {
+  send: {
+    msg?: Binary | null;
+  };
+} | {
+  send_and_notify: {
+    msg: Binary;
+  };
+} | {
+  transfer: Record<string, never>;
+};
+
+export type ExpiryDestination = {
+  redistribute: Record<string, never>;
+} | {
+  treasury: {
+    address: HumanAddr;
+  };
+};
+
+export type GaugeWeight = {
+  recipient: HumanAddr;
+  weight: Uint128;
+};
+
+export type HumanAddr = string;
+
+export type KeeperBounty = {
+  basis_points: {
+    basis_points: number;
+    cap: Uint128;
+  };
+} | {
+  fixed: {
+    amount: Uint128;
+  };
+};
+
+export type PendingRecipientInfo = {
+  contract: SecretContract;
+  delivery_mode: DeliveryMode;
+  replaces?: HumanAddr | null;
+  weight: Uint128;
+};
+
+export type Proposal = {
+  approvals: HumanAddr[];
+  executed: boolean;
+  expires_at_block: number;
+  id: number;
+  msg: ButtcoinDistributorHandleMsg;
+};
+
+export type RecipientInfo = {
+  accrual_buckets: AccrualBucket[];
+  accrued: Uint128;
+  bucketed_until_block: number;
+  claim_epoch: number;
+  claim_operators: HumanAddr[];
+  claimed_in_epoch: Uint128;
+  contract: SecretContract;
+  delivery_mode: DeliveryMode;
+  hook_variants?: string[] | null;
+  reward_debt: Uint128;
+  unexpiring: Uint128;
+  weight: Uint128;
+};
+
+export type SecretContract = {
+  address: HumanAddr;
+  contract_hash: string;
+};
+
+export type Uint128 = string;
+
+export type VoteAllocation = {
+  basis_points: number;
+  recipient: HumanAddr;
+};
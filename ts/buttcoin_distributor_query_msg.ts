@@ -0,0 +1,42 @@
+// Generated from schema/buttcoin_distributor_query_msg.json by `cargo schema`. Do not edit by hand.
+
+export type ButtcoinDistributorQueryMsg = {
+  claim_limits: Record<string, never>;
+} | {
+  config: Record<string, never>;
+} | {
+  expiring_rewards: {
+    recipient: HumanAddr;
+  };
+} | {
+  fee_stats: Record<string, never>;
+} | {
+  gauge_weights: {
+    epoch: number;
+  };
+} | {
+  keeper: Record<string, never>;
+} | {
+  pending: {
+    block: number;
+    recipient?: HumanAddr | null;
+  };
+} | {
+  pending_recipients: Record<string, never>;
+} | {
+  proposal: {
+    id: number;
+  };
+} | {
+  proposals: Record<string, never>;
+} | {
+  recipients: Record<string, never>;
+} | {
+  reward_expiry: Record<string, never>;
+} | {
+  votes: {
+    voter: HumanAddr;
+  };
+};
+
+export type HumanAddr = string;
@@ -0,0 +1,5 @@
+// Generated from schema/buttcoin_distributor_receive_msg.json by `cargo schema`. Do not edit by hand.
+
+export type ButtcoinDistributorReceiveMsg = {
+  lock_buttcoin: Record<string, never>;
+};
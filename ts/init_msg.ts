@@ -0,0 +1,20 @@
+// Generated from schema/init_msg.json by `cargo schema`. Do not edit by hand.
+
+export type InitMsg = {
+  buttcoin: SecretContract;
+  end_block: number;
+  max_total_emission?: Uint128 | null;
+  release_per_block: Uint128;
+  release_per_block_denominator?: number | null;
+  starting_block: number;
+  viewing_key: string;
+};
+
+export type HumanAddr = string;
+
+export type SecretContract = {
+  address: HumanAddr;
+  contract_hash: string;
+};
+
+export type Uint128 = string;
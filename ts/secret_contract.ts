@@ -0,0 +1,8 @@
+// Generated from schema/secret_contract.json by `cargo schema`. Do not edit by hand.
+
+export type SecretContract = {
+  address: HumanAddr;
+  contract_hash: string;
+};
+
+export type HumanAddr = string;
@@ -0,0 +1,36 @@
+// Generated from schema/state.json by `cargo schema`. Do not edit by hand.
+
+export type State = {
+  acc_buttcoin_per_weight: Uint128;
+  admin: HumanAddr;
+  buttcoin: CanonicalSecretContract;
+  end_block: number;
+  frozen: boolean;
+  gauge_voting: boolean;
+  last_update_block: number;
+  max_total_emission: Uint128;
+  receivable_smart_contract?: CanonicalSecretContract | null;
+  release_per_block: Uint128;
+  release_per_block_denominator: number;
+  reward_remainder: Uint128;
+  schedule_checkpoint_block: number;
+  starting_block: number;
+  total_emitted: Uint128;
+  total_funded: Uint128;
+  total_weight: Uint128;
+  unallocated_rewards: Uint128;
+  viewing_key: string;
+};
+
+export type Binary = string;
+
+export type CanonicalAddr = Binary;
+
+export type CanonicalSecretContract = {
+  address: CanonicalAddr;
+  contract_hash: string;
+};
+
+export type HumanAddr = string;
+
+export type Uint128 = string;
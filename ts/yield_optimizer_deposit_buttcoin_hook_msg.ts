@@ -0,0 +1,17 @@
+// Generated from schema/yield_optimizer_deposit_buttcoin_hook_msg.json by `cargo schema`. Do not edit by hand.
+
+export type YieldOptimizerDepositButtcoinHookMsg = {
+  continue_deposit_after_buttcoin_claimed: {
+    depositer: HumanAddr;
+    incentivized_token_amount: Uint128;
+  };
+} | {
+  continue_withdrawal_after_buttcoin_claimed: {
+    shares_amount: Uint128;
+    withdrawer: HumanAddr;
+  };
+};
+
+export type HumanAddr = string;
+
+export type Uint128 = string;
@@ -0,0 +1,9 @@
+// Generated from schema/yield_optimizer_receive_msg.json by `cargo schema`. Do not edit by hand.
+
+export type YieldOptimizerReceiveMsg = {
+  deposit_buttcoin: {
+    hook?: Binary | null;
+  };
+};
+
+export type Binary = string;